@@ -1,5 +1,6 @@
 use near_sdk::{
-    assert_one_yocto, env, ext_contract, near, require, AccountId, NearToken, Promise, PublicKey,
+    assert_one_yocto, env, ext_contract, json_types::Base58CryptoHash, near, require, AccountId,
+    Gas, GasWeight, NearToken, Promise, PublicKey,
 };
 
 #[allow(dead_code)]
@@ -13,7 +14,6 @@ trait IntentsContract {
 #[near(contract_state)]
 pub struct Contract {}
 
-/// TODO: the contract can be deployed as a global contract
 #[near]
 impl Contract {
     #[payable]
@@ -43,6 +43,31 @@ impl Contract {
             .with_attached_deposit(NearToken::from_yoctonear(1))
             .remove_public_key(public_key)
     }
+
+    /// Upgrade the vault to the global contract with the code hash (parent account only)
+    #[payable]
+    pub fn upgrade(&mut self, code_hash: Base58CryptoHash) -> Promise {
+        assert_one_yocto();
+        self.require_parent_account();
+
+        Promise::new(env::current_account_id())
+            .use_global_contract(code_hash)
+            .function_call_weight(
+                "migrate".into(),
+                vec![],
+                NearToken::from_yoctonear(0),
+                Gas::from_tgas(0),
+                GasWeight(1),
+            )
+    }
+
+    #[init(ignore_state)]
+    #[private]
+    pub fn migrate() -> Self {
+        #[cfg(feature = "test-upgrade")]
+        near_sdk::log!("Migrated to the test upgrade");
+        env::state_read::<Self>().unwrap_or_default()
+    }
 }
 
 impl Contract {
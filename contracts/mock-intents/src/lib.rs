@@ -1,6 +1,7 @@
-use near_sdk::store::LookupMap;
+use near_sdk::store::{LookupMap, LookupSet};
 use near_sdk::{
-    assert_one_yocto, env, near, AccountId, BorshStorageKey, PanicOnDefault, PublicKey,
+    assert_one_yocto, env, near, require, AccountId, BorshStorageKey, PanicOnDefault, Promise,
+    PublicKey,
 };
 use std::collections::HashSet;
 
@@ -10,12 +11,15 @@ mod token_receiver;
 #[near(contract_state)]
 pub struct Contract {
     public_keys: LookupMap<AccountId, HashSet<PublicKey>>,
+    /// Public keys whose removal fails, to test the failure handling of the callers
+    failing_removals: LookupSet<PublicKey>,
 }
 
 #[near]
 #[derive(BorshStorageKey)]
 pub enum Prefix {
     PublicKeys,
+    FailingRemovals,
 }
 
 #[near]
@@ -25,6 +29,7 @@ impl Contract {
     pub fn new() -> Self {
         Self {
             public_keys: LookupMap::new(Prefix::PublicKeys),
+            failing_removals: LookupSet::new(Prefix::FailingRemovals),
         }
     }
 
@@ -41,12 +46,32 @@ impl Contract {
     #[payable]
     pub fn remove_public_key(&mut self, public_key: PublicKey) {
         assert_one_yocto();
+        require!(
+            !self.failing_removals.contains(&public_key),
+            "Public key removal failed"
+        );
         let account_id = env::predecessor_account_id();
         let mut keys = self.internal_get_account(&account_id);
         keys.remove(&public_key);
         self.public_keys.insert(account_id, keys.clone());
     }
 
+    /// Publish the code passed as the input as a global contract identified by its hash, to test
+    /// the upgrades of the vaults to other code
+    pub fn deploy_global_contract(&mut self) -> Promise {
+        let code = env::input().expect("Code not found");
+        Promise::new(env::current_account_id()).deploy_global_contract(code)
+    }
+
+    /// Make the removal of the public key fail, or succeed again
+    pub fn set_public_key_removal_failing(&mut self, public_key: PublicKey, failing: bool) {
+        if failing {
+            self.failing_removals.insert(public_key);
+        } else {
+            self.failing_removals.remove(&public_key);
+        }
+    }
+
     pub fn public_keys_of(&self, account_id: AccountId) -> HashSet<PublicKey> {
         self.internal_get_account(&account_id)
     }
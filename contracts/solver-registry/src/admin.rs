@@ -3,11 +3,334 @@ use near_sdk::near;
 
 #[near]
 impl Contract {
-    /// Approve a docker compose hash for worker registration
-    pub fn approve_compose_hash(&mut self, compose_hash: String) {
-        self.assert_owner();
+    /// Approve a docker compose hash for worker registration. Scheduled if the timelock delay is
+    /// set, in which case the ID of the scheduled action is returned.
+    pub fn approve_compose_hash(&mut self, compose_hash: String) -> Option<ActionId> {
+        self.assert_role(Role::ComposeHashManager);
+        self.assert_multisig_disabled();
+        DockerComposeHash::try_from_hex(compose_hash.clone()).expect("Invalid compose hash");
+
+        self.schedule_or_execute(TimelockAction::ApproveComposeHash { compose_hash })
+    }
+
+    /// Remove an approved docker compose hash
+    pub fn remove_compose_hash(&mut self, compose_hash: String) {
+        self.assert_role(Role::ComposeHashManager);
+        self.assert_multisig_disabled();
         DockerComposeHash::try_from_hex(compose_hash.clone()).expect("Invalid compose hash");
 
+        self.apply_remove_compose_hash(compose_hash);
+    }
+
+    /// Approve a pre-launch script to run with an approved docker compose hash. Scheduled if the
+    /// timelock delay is set.
+    pub fn approve_pre_launch_script(
+        &mut self,
+        compose_hash: String,
+        pre_launch_script_hash: String,
+    ) -> Option<ActionId> {
+        self.assert_role(Role::ComposeHashManager);
+        self.assert_multisig_disabled();
+        self.assert_approved_compose_hash(&compose_hash);
+        PreLaunchScriptHash::try_from_hex(pre_launch_script_hash.clone())
+            .expect("Invalid pre-launch script hash");
+
+        self.schedule_or_execute(TimelockAction::ApprovePreLaunchScript {
+            compose_hash,
+            pre_launch_script_hash,
+        })
+    }
+
+    /// Remove an approved pre-launch script of a docker compose hash
+    pub fn remove_pre_launch_script(
+        &mut self,
+        compose_hash: String,
+        pre_launch_script_hash: String,
+    ) {
+        self.assert_role(Role::ComposeHashManager);
+        self.assert_multisig_disabled();
+
+        self.apply_remove_pre_launch_script(compose_hash, pre_launch_script_hash);
+    }
+
+    /// Approve a docker image digest to run with an approved docker compose hash. Once any docker
+    /// image is approved, workers must measure an approved digest in the docker image hash event.
+    /// Scheduled if the timelock delay is set.
+    pub fn approve_docker_image_hash(
+        &mut self,
+        compose_hash: String,
+        docker_image_hash: String,
+    ) -> Option<ActionId> {
+        self.assert_role(Role::ComposeHashManager);
+        self.assert_multisig_disabled();
+        self.assert_approved_compose_hash(&compose_hash);
+        DockerImageHash::try_from_hex(docker_image_hash.clone())
+            .expect("Invalid docker image hash");
+
+        self.schedule_or_execute(TimelockAction::ApproveDockerImageHash {
+            compose_hash,
+            docker_image_hash,
+        })
+    }
+
+    /// Remove an approved docker image digest of a docker compose hash. Removing the last one
+    /// lets any docker image run with the docker compose hash if the docker image hash event isn't
+    /// set, so the removal is scheduled if the timelock delay is set.
+    pub fn remove_docker_image_hash(
+        &mut self,
+        compose_hash: String,
+        docker_image_hash: String,
+    ) -> Option<ActionId> {
+        self.assert_role(Role::ComposeHashManager);
+        self.assert_multisig_disabled();
+        require!(
+            self.get_compose_hash_config_or_default(&compose_hash)
+                .docker_image_hashes
+                .contains(&docker_image_hash),
+            "Docker image hash not found"
+        );
+
+        self.schedule_or_execute(TimelockAction::RemoveDockerImageHash {
+            compose_hash,
+            docker_image_hash,
+        })
+    }
+
+    /// Set the name of the RTMR3 event which measures the solver's docker image digest. Once set,
+    /// workers are accepted only if the event measures an approved docker image of the docker
+    /// compose hash. Set `None` to skip the docker image verification, in which case the docker
+    /// compose hashes with approved docker images can't be used. Scheduled if the timelock delay
+    /// is set.
+    pub fn set_docker_image_hash_event(
+        &mut self,
+        docker_image_hash_event: Option<String>,
+    ) -> Option<ActionId> {
+        self.assert_owner();
+        require!(
+            docker_image_hash_event
+                .as_ref()
+                .is_none_or(|event_name| !event_name.is_empty()),
+            "Invalid docker image hash event"
+        );
+
+        self.schedule_or_execute(TimelockAction::SetDockerImageHashEvent {
+            docker_image_hash_event,
+        })
+    }
+
+    /// Set the allowlist of environment variable names for CVMs running an approved docker compose
+    /// hash. Set `None` to use the allowlist of the app compose policy. Scheduled if the timelock
+    /// delay is set.
+    pub fn set_compose_hash_allowed_envs(
+        &mut self,
+        compose_hash: String,
+        allowed_envs: Option<Vec<String>>,
+    ) -> Option<ActionId> {
+        self.assert_role(Role::ComposeHashManager);
+        self.assert_multisig_disabled();
+        self.assert_approved_compose_hash(&compose_hash);
+        if let Some(allowed_envs) = allowed_envs.as_ref() {
+            Self::assert_valid_env_names(allowed_envs);
+        }
+
+        self.schedule_or_execute(TimelockAction::SetComposeHashAllowedEnvs {
+            compose_hash,
+            allowed_envs,
+        })
+    }
+
+    /// Update the policy of accepted TCB statuses and tolerated security advisories. Scheduled if
+    /// the timelock delay is set.
+    pub fn set_tcb_policy(&mut self, tcb_policy: TcbPolicy) -> Option<ActionId> {
+        self.assert_owner();
+        require!(
+            !tcb_policy.accepted_statuses.is_empty(),
+            "At least one TCB status must be accepted"
+        );
+
+        self.schedule_or_execute(TimelockAction::SetTcbPolicy { tcb_policy })
+    }
+
+    /// Update the global app compose policy for workers. Scheduled if the timelock delay is set.
+    pub fn set_app_compose_policy(
+        &mut self,
+        app_compose_policy: AppComposePolicy,
+    ) -> Option<ActionId> {
+        self.assert_owner();
+        Self::assert_valid_app_compose_policy(&app_compose_policy);
+
+        self.schedule_or_execute(TimelockAction::SetAppComposePolicy { app_compose_policy })
+    }
+
+    /// Override the app compose policy for workers of a pool. Set `None` to use the global policy.
+    /// Scheduled if the timelock delay is set.
+    pub fn set_pool_app_compose_policy(
+        &mut self,
+        pool_id: u32,
+        app_compose_policy: Option<AppComposePolicy>,
+    ) -> Option<ActionId> {
+        self.assert_role(Role::PoolManager);
+        require!(self.pool(pool_id).is_some(), "Pool not found");
+        if let Some(policy) = app_compose_policy.as_ref() {
+            Self::assert_valid_app_compose_policy(policy);
+        }
+
+        self.schedule_or_execute(TimelockAction::SetPoolAppComposePolicy {
+            pool_id,
+            app_compose_policy,
+        })
+    }
+
+    /// Set the global worker ping timeout, after which the worker of a pool is considered
+    /// inactive and can be replaced. Applies to the pools which don't override it.
+    pub fn set_worker_ping_timeout_ms(&mut self, worker_ping_timeout_ms: TimestampMs) {
+        self.assert_owner();
+        require!(worker_ping_timeout_ms > 0, "Invalid worker ping timeout");
+
+        self.worker_ping_timeout_ms = worker_ping_timeout_ms;
+
+        Event::WorkerPingTimeoutUpdated {
+            worker_ping_timeout_ms: &self.worker_ping_timeout_ms,
+        }
+        .emit();
+    }
+
+    /// Override the worker ping timeout of a pool. Set `None` to use the global timeout.
+    pub fn set_pool_worker_ping_timeout_ms(
+        &mut self,
+        pool_id: u32,
+        worker_ping_timeout_ms: Option<TimestampMs>,
+    ) {
+        self.assert_role(Role::PoolManager);
+        require!(
+            worker_ping_timeout_ms != Some(0),
+            "Invalid worker ping timeout"
+        );
+
+        let pool = self.pool_mut(pool_id).expect("Pool not found");
+        pool.worker_ping_timeout_ms = worker_ping_timeout_ms;
+        self.pools.flush();
+
+        Event::PoolWorkerPingTimeoutUpdated {
+            pool_id: &pool_id,
+            worker_ping_timeout_ms: &worker_ping_timeout_ms,
+        }
+        .emit();
+    }
+
+    /// Set how long a registration challenge stays valid after it's issued
+    pub fn set_registration_challenge_ttl_ms(
+        &mut self,
+        registration_challenge_ttl_ms: TimestampMs,
+    ) {
+        self.assert_owner();
+        require!(
+            registration_challenge_ttl_ms > 0,
+            "Invalid registration challenge TTL"
+        );
+
+        self.registration_challenge_ttl_ms = registration_challenge_ttl_ms;
+
+        Event::RegistrationChallengeTtlUpdated {
+            registration_challenge_ttl_ms: &self.registration_challenge_ttl_ms,
+        }
+        .emit();
+    }
+
+    /// Allow or disallow workers to register without a registration challenge, with the report
+    /// data V1 which only commits to the worker's public key. Scheduled if the timelock delay is
+    /// set.
+    pub fn set_allow_legacy_report_data(
+        &mut self,
+        allow_legacy_report_data: bool,
+    ) -> Option<ActionId> {
+        self.assert_owner();
+
+        self.schedule_or_execute(TimelockAction::SetAllowLegacyReportData {
+            allow_legacy_report_data,
+        })
+    }
+
+    /// Set the curve types allowed for workers' intents public keys. Scheduled if the timelock
+    /// delay is set.
+    pub fn set_allowed_key_types(&mut self, allowed_key_types: Vec<KeyType>) -> Option<ActionId> {
+        self.assert_owner();
+        require!(
+            !allowed_key_types.is_empty(),
+            "At least one key type must be allowed"
+        );
+
+        self.schedule_or_execute(TimelockAction::SetAllowedKeyTypes { allowed_key_types })
+    }
+
+    /// Set the maximum age of the collateral of workers' attestations, i.e. the time since the
+    /// collateral was issued by Intel. Scheduled if the timelock delay is set.
+    pub fn set_max_collateral_age_ms(
+        &mut self,
+        max_collateral_age_ms: TimestampMs,
+    ) -> Option<ActionId> {
+        self.assert_owner();
+        require!(max_collateral_age_ms >= 1000, "Invalid max collateral age");
+
+        self.schedule_or_execute(TimelockAction::SetMaxCollateralAge {
+            max_collateral_age_ms,
+        })
+    }
+
+    /// Propose a new owner, who must accept the ownership with `accept_ownership`. Replaces the
+    /// pending proposal if exists.
+    pub fn propose_owner(&mut self, new_owner_id: AccountId) {
+        self.assert_owner();
+        require!(
+            new_owner_id != self.owner_id,
+            "New owner must be different from the current owner"
+        );
+
+        self.pending_owner_id = Some(new_owner_id.clone());
+
+        Event::OwnershipTransferProposed {
+            owner_id: &self.owner_id,
+            pending_owner_id: &new_owner_id,
+        }
+        .emit();
+    }
+
+    /// Accept the ownership proposed to the caller
+    pub fn accept_ownership(&mut self) {
+        let new_owner_id = env::predecessor_account_id();
+        require!(
+            self.pending_owner_id.as_ref() == Some(&new_owner_id),
+            "Only the pending owner can accept the ownership"
+        );
+
+        self.pending_owner_id = None;
+        let old_owner_id = std::mem::replace(&mut self.owner_id, new_owner_id);
+
+        Event::OwnerChanged {
+            old_owner_id: &old_owner_id,
+            new_owner_id: &self.owner_id,
+        }
+        .emit();
+    }
+
+    /// Cancel the pending ownership proposal
+    pub fn cancel_ownership_proposal(&mut self) {
+        self.assert_owner();
+        let pending_owner_id = self
+            .pending_owner_id
+            .take()
+            .expect("No pending ownership proposal");
+
+        Event::OwnershipProposalCancelled {
+            owner_id: &self.owner_id,
+            pending_owner_id: &pending_owner_id,
+        }
+        .emit();
+    }
+}
+
+impl Contract {
+    pub(crate) fn apply_approve_compose_hash(&mut self, compose_hash: String) {
         self.approved_compose_hashes.insert(compose_hash.clone());
 
         Event::ComposeHashApproved {
@@ -16,15 +339,12 @@ impl Contract {
         .emit();
     }
 
-    /// Remove an approved docker compose hash
-    pub fn remove_compose_hash(&mut self, compose_hash: String) {
-        self.assert_owner();
-        DockerComposeHash::try_from_hex(compose_hash.clone()).expect("Invalid compose hash");
-
+    pub(crate) fn apply_remove_compose_hash(&mut self, compose_hash: String) {
         require!(
             self.approved_compose_hashes.remove(&compose_hash),
             "Compose hash not found"
         );
+        self.compose_hash_configs.remove(&compose_hash);
 
         Event::ComposeHashRemoved {
             compose_hash: &compose_hash,
@@ -32,21 +352,215 @@ impl Contract {
         .emit();
     }
 
-    pub fn change_owner(&mut self, new_owner_id: AccountId) {
-        self.assert_owner();
-        let old_owner_id = self.owner_id.clone();
-        self.owner_id = new_owner_id.clone();
+    pub(crate) fn apply_approve_pre_launch_script(
+        &mut self,
+        compose_hash: String,
+        pre_launch_script_hash: String,
+    ) {
+        self.assert_approved_compose_hash(&compose_hash);
 
-        Event::OwnerChanged {
-            old_owner_id: &old_owner_id,
-            new_owner_id: &new_owner_id,
+        let mut config = self.get_compose_hash_config_or_default(&compose_hash);
+        require!(
+            !config
+                .pre_launch_script_hashes
+                .contains(&pre_launch_script_hash),
+            "Pre-launch script already approved"
+        );
+        config
+            .pre_launch_script_hashes
+            .push(pre_launch_script_hash.clone());
+        self.compose_hash_configs
+            .insert(compose_hash.clone(), config);
+
+        Event::PreLaunchScriptApproved {
+            compose_hash: &compose_hash,
+            pre_launch_script_hash: &pre_launch_script_hash,
+        }
+        .emit();
+    }
+
+    pub(crate) fn apply_remove_pre_launch_script(
+        &mut self,
+        compose_hash: String,
+        pre_launch_script_hash: String,
+    ) {
+        let mut config = self.get_compose_hash_config_or_default(&compose_hash);
+        let len = config.pre_launch_script_hashes.len();
+        config
+            .pre_launch_script_hashes
+            .retain(|hash| hash != &pre_launch_script_hash);
+        require!(
+            config.pre_launch_script_hashes.len() < len,
+            "Pre-launch script not found"
+        );
+        self.compose_hash_configs
+            .insert(compose_hash.clone(), config);
+
+        Event::PreLaunchScriptRemoved {
+            compose_hash: &compose_hash,
+            pre_launch_script_hash: &pre_launch_script_hash,
+        }
+        .emit();
+    }
+
+    pub(crate) fn apply_approve_docker_image_hash(
+        &mut self,
+        compose_hash: String,
+        docker_image_hash: String,
+    ) {
+        self.assert_approved_compose_hash(&compose_hash);
+
+        let mut config = self.get_compose_hash_config_or_default(&compose_hash);
+        require!(
+            !config.docker_image_hashes.contains(&docker_image_hash),
+            "Docker image hash already approved"
+        );
+        config.docker_image_hashes.push(docker_image_hash.clone());
+        self.compose_hash_configs
+            .insert(compose_hash.clone(), config);
+
+        Event::DockerImageHashApproved {
+            compose_hash: &compose_hash,
+            docker_image_hash: &docker_image_hash,
+        }
+        .emit();
+    }
+
+    pub(crate) fn apply_remove_docker_image_hash(
+        &mut self,
+        compose_hash: String,
+        docker_image_hash: String,
+    ) {
+        let mut config = self.get_compose_hash_config_or_default(&compose_hash);
+        let len = config.docker_image_hashes.len();
+        config
+            .docker_image_hashes
+            .retain(|hash| hash != &docker_image_hash);
+        require!(
+            config.docker_image_hashes.len() < len,
+            "Docker image hash not found"
+        );
+        self.compose_hash_configs
+            .insert(compose_hash.clone(), config);
+
+        Event::DockerImageHashRemoved {
+            compose_hash: &compose_hash,
+            docker_image_hash: &docker_image_hash,
+        }
+        .emit();
+    }
+
+    pub(crate) fn apply_set_compose_hash_allowed_envs(
+        &mut self,
+        compose_hash: String,
+        allowed_envs: Option<Vec<String>>,
+    ) {
+        self.assert_approved_compose_hash(&compose_hash);
+
+        let mut config = self.get_compose_hash_config_or_default(&compose_hash);
+        config.allowed_envs = allowed_envs.clone();
+        self.compose_hash_configs
+            .insert(compose_hash.clone(), config);
+
+        Event::ComposeHashAllowedEnvsUpdated {
+            compose_hash: &compose_hash,
+            allowed_envs: &allowed_envs,
+        }
+        .emit();
+    }
+
+    pub(crate) fn apply_set_app_compose_policy(&mut self, app_compose_policy: AppComposePolicy) {
+        self.app_compose_policy = app_compose_policy;
+
+        Event::AppComposePolicyUpdated {
+            app_compose_policy: &self.app_compose_policy,
+        }
+        .emit();
+    }
+
+    pub(crate) fn apply_set_pool_app_compose_policy(
+        &mut self,
+        pool_id: u32,
+        app_compose_policy: Option<AppComposePolicy>,
+    ) {
+        let pool = self.pool_mut(pool_id).expect("Pool not found");
+        pool.app_compose_policy = app_compose_policy.clone();
+        self.pools.flush();
+
+        Event::PoolAppComposePolicyUpdated {
+            pool_id: &pool_id,
+            app_compose_policy: &app_compose_policy,
+        }
+        .emit();
+    }
+
+    pub(crate) fn apply_set_allow_legacy_report_data(&mut self, allow_legacy_report_data: bool) {
+        self.allow_legacy_report_data = allow_legacy_report_data;
+
+        Event::LegacyReportDataUpdated {
+            allow_legacy_report_data: &self.allow_legacy_report_data,
+        }
+        .emit();
+    }
+
+    pub(crate) fn apply_set_allowed_key_types(&mut self, allowed_key_types: Vec<KeyType>) {
+        self.allowed_key_types = allowed_key_types;
+
+        Event::AllowedKeyTypesUpdated {
+            allowed_key_types: &self.allowed_key_types,
+        }
+        .emit();
+    }
+
+    pub(crate) fn apply_set_max_collateral_age(&mut self, max_collateral_age_ms: TimestampMs) {
+        self.max_collateral_age_ms = max_collateral_age_ms;
+
+        Event::MaxCollateralAgeUpdated {
+            max_collateral_age_ms: &self.max_collateral_age_ms,
+        }
+        .emit();
+    }
+
+    pub(crate) fn apply_set_docker_image_hash_event(
+        &mut self,
+        docker_image_hash_event: Option<String>,
+    ) {
+        self.docker_image_hash_event = docker_image_hash_event;
+
+        Event::DockerImageHashEventUpdated {
+            docker_image_hash_event: &self.docker_image_hash_event,
+        }
+        .emit();
+    }
+
+    pub(crate) fn apply_set_tcb_policy(&mut self, tcb_policy: TcbPolicy) {
+        self.tcb_policy = tcb_policy;
+
+        Event::TcbPolicyUpdated {
+            tcb_policy: &self.tcb_policy,
         }
         .emit();
     }
-}
 
-impl Contract {
     pub(crate) fn assert_owner(&self) {
         require!(env::predecessor_account_id() == self.owner_id);
     }
+
+    fn assert_valid_app_compose_policy(app_compose_policy: &AppComposePolicy) {
+        if let Some(allowed_envs) = app_compose_policy.allowed_envs.as_ref() {
+            Self::assert_valid_env_names(allowed_envs);
+        }
+    }
+
+    pub(crate) fn assert_valid_env_names(env_names: &[String]) {
+        for env_name in env_names {
+            require!(
+                !env_name.is_empty()
+                    && env_name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_'),
+                format!("Invalid environment variable name: {}", env_name)
+            );
+        }
+    }
 }
@@ -2,6 +2,9 @@ use alloc::{string::String, vec::Vec};
 use borsh::{BorshDeserialize, BorshSerialize};
 use derive_more::{Deref, From};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::hash::{AppComposeHash, DockerComposeHash};
 
 #[cfg(all(feature = "abi", not(target_arch = "wasm32")))]
 use alloc::string::ToString;
@@ -47,3 +50,47 @@ pub struct AppCompose {
 )]
 #[derive(Debug, Deserialize, Serialize, BorshSerialize, BorshDeserialize, From, Deref)]
 pub struct DockerComposeString(String);
+
+impl AppCompose {
+    /// Hash of the docker compose file, which is approved in the registry with
+    /// `approve_compose_hash`.
+    pub fn docker_compose_hash(&self) -> DockerComposeHash {
+        self.docker_compose_file.compose_hash()
+    }
+
+    /// Hash of the raw app compose JSON, which is measured into RTMR3 as the `compose-hash` event.
+    /// The hash is computed over the exact JSON string, so it must not be re-serialized.
+    pub fn app_compose_hash(app_compose_json: &str) -> AppComposeHash {
+        AppComposeHash::from(<[u8; 32]>::from(Sha256::digest(
+            app_compose_json.as_bytes(),
+        )))
+    }
+}
+
+impl DockerComposeString {
+    /// Hash of the docker compose file.
+    pub fn compose_hash(&self) -> DockerComposeHash {
+        DockerComposeHash::from(<[u8; 32]>::from(Sha256::digest(self.0.as_bytes())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dstack_sdk_types::dstack::TcbInfo;
+
+    const TCB_INFO: &str = include_str!("../../tests/samples/alice/tcb_info.json");
+    const COMPOSE_HASH: &str = "f68cf65a75ad980289144ef3c096a007fc4583ea6d1f90589757f229dbc6cdab";
+
+    #[test]
+    fn test_compose_hashes() {
+        let tcb_info: TcbInfo = serde_json::from_str(TCB_INFO).unwrap();
+        let app_compose: AppCompose = serde_json::from_str(&tcb_info.app_compose).unwrap();
+
+        assert_eq!(app_compose.docker_compose_hash().as_hex(), COMPOSE_HASH);
+        assert_eq!(
+            AppCompose::app_compose_hash(&tcb_info.app_compose).as_hex(),
+            tcb_info.compose_hash
+        );
+    }
+}
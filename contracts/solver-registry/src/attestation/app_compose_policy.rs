@@ -0,0 +1,129 @@
+use near_sdk::near;
+
+use super::app_compose::AppCompose;
+
+/// Security requirements for the app compose configuration of a worker's CVM. Every `Some` value
+/// is required to match the value in the app compose, while `None` accepts any value.
+#[near(serializers = [json, borsh])]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppComposePolicy {
+    pub manifest_version: Option<u32>,
+    pub runner: Option<String>,
+    pub kms_enabled: Option<bool>,
+    /// Gateway (a.k.a. tproxy) enablement. `gateway_enabled` falls back to `tproxy_enabled`.
+    pub gateway_enabled: Option<bool>,
+    pub local_key_provider_enabled: Option<bool>,
+    pub public_logs: Option<bool>,
+    pub public_sysinfo: Option<bool>,
+    pub no_instance_id: Option<bool>,
+    /// Secure time is enabled by default in dstack when not set in the app compose.
+    pub secure_time: Option<bool>,
+    /// Allowlist of environment variable names which can be passed to the CVM.
+    pub allowed_envs: Option<Vec<String>>,
+}
+
+impl Default for AppComposePolicy {
+    /// The configuration of TEE Solver's CVMs in Phala Cloud. Find more details in
+    /// `attestation/README.md`.
+    fn default() -> Self {
+        Self {
+            manifest_version: Some(2),
+            runner: Some("docker-compose".to_string()),
+            // Note: require KMS enabled with dstack v0.5+ in Phala Cloud
+            kms_enabled: Some(true),
+            gateway_enabled: None,
+            local_key_provider_enabled: None,
+            public_logs: Some(true),
+            public_sysinfo: Some(true),
+            no_instance_id: None,
+            secure_time: Some(true),
+            allowed_envs: None,
+        }
+    }
+}
+
+impl AppComposePolicy {
+    /// Validates the app compose configuration against the policy.
+    pub fn validate(&self, app_compose: &AppCompose) -> bool {
+        let gateway_enabled = app_compose
+            .gateway_enabled
+            .or(app_compose.tproxy_enabled)
+            .unwrap_or(false);
+
+        Self::matches(self.manifest_version, app_compose.manifest_version)
+            && self
+                .runner
+                .as_ref()
+                .is_none_or(|runner| runner == &app_compose.runner)
+            && Self::matches(self.kms_enabled, app_compose.kms_enabled)
+            && Self::matches(self.gateway_enabled, gateway_enabled)
+            && Self::matches(
+                self.local_key_provider_enabled,
+                app_compose.local_key_provider_enabled,
+            )
+            && Self::matches(self.public_logs, app_compose.public_logs)
+            && Self::matches(self.public_sysinfo, app_compose.public_sysinfo)
+            && Self::matches(self.no_instance_id, app_compose.no_instance_id)
+            && Self::matches(self.secure_time, app_compose.secure_time.unwrap_or(true))
+            && self.validate_allowed_envs(&app_compose.allowed_envs)
+    }
+
+    fn matches<T: PartialEq>(required: Option<T>, actual: T) -> bool {
+        required.is_none_or(|required| required == actual)
+    }
+
+    fn validate_allowed_envs(&self, allowed_envs: &[String]) -> bool {
+        self.allowed_envs.as_ref().is_none_or(|allowlist| {
+            allowed_envs
+                .iter()
+                .all(|env_name| allowlist.contains(env_name))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dstack_sdk_types::dstack::TcbInfo;
+
+    const TCB_INFO: &str = include_str!("../../tests/samples/alice/tcb_info.json");
+
+    fn app_compose() -> AppCompose {
+        let tcb_info: TcbInfo = serde_json::from_str(TCB_INFO).unwrap();
+        serde_json::from_str(&tcb_info.app_compose).unwrap()
+    }
+
+    #[test]
+    fn test_default_policy() {
+        assert!(AppComposePolicy::default().validate(&app_compose()));
+    }
+
+    #[test]
+    fn test_required_values() {
+        let policy = AppComposePolicy {
+            gateway_enabled: Some(false),
+            ..Default::default()
+        };
+        assert!(!policy.validate(&app_compose()));
+
+        let policy = AppComposePolicy {
+            local_key_provider_enabled: Some(false),
+            no_instance_id: Some(false),
+            ..Default::default()
+        };
+        assert!(policy.validate(&app_compose()));
+    }
+
+    #[test]
+    fn test_allowed_envs() {
+        let app_compose = app_compose();
+        let mut policy = AppComposePolicy {
+            allowed_envs: Some(app_compose.allowed_envs.clone()),
+            ..Default::default()
+        };
+        assert!(policy.validate(&app_compose));
+
+        policy.allowed_envs.as_mut().unwrap().pop();
+        assert!(!policy.validate(&app_compose));
+    }
+}
@@ -1,4 +1,4 @@
-use alloc::{string::String, vec::Vec};
+use alloc::{format, string::String, vec::Vec};
 use borsh::{BorshDeserialize, BorshSerialize};
 use core::str::FromStr;
 use dcap_qvl::QuoteCollateralV3;
@@ -63,6 +63,115 @@ impl Collateral {
     }
 }
 
+/// Issue date and next update of a collateral item, in seconds since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollateralDates {
+    pub issue_date_s: u64,
+    pub next_update_s: u64,
+}
+
+impl Collateral {
+    /// Lowercase hex-encoded FMSPC (Family-Model-Stepping-Platform-CustomSKU) of the platforms
+    /// which the TCB info applies to.
+    pub fn fmspc(&self) -> Result<String, CollateralError> {
+        let value: Value =
+            serde_json::from_str(&self.0.tcb_info).map_err(|_| CollateralError::InvalidJson)?;
+        value
+            .get("fmspc")
+            .and_then(Value::as_str)
+            .map(str::to_ascii_lowercase)
+            .ok_or_else(|| CollateralError::MissingField(String::from("tcb_info.fmspc")))
+    }
+
+    /// Dates of the TCB info.
+    pub fn tcb_info_dates(&self) -> Result<CollateralDates, CollateralError> {
+        Self::parse_dates("tcb_info", &self.0.tcb_info)
+    }
+
+    /// Dates of the QE identity.
+    pub fn qe_identity_dates(&self) -> Result<CollateralDates, CollateralError> {
+        Self::parse_dates("qe_identity", &self.0.qe_identity)
+    }
+
+    /// Dates of the collateral as a whole, i.e. the oldest issue date and the earliest next update
+    /// of the TCB info and the QE identity.
+    pub fn dates(&self) -> Result<CollateralDates, CollateralError> {
+        let tcb_info = self.tcb_info_dates()?;
+        let qe_identity = self.qe_identity_dates()?;
+        Ok(CollateralDates {
+            issue_date_s: tcb_info.issue_date_s.min(qe_identity.issue_date_s),
+            next_update_s: tcb_info.next_update_s.min(qe_identity.next_update_s),
+        })
+    }
+
+    fn parse_dates(field: &str, json: &str) -> Result<CollateralDates, CollateralError> {
+        let value: Value = serde_json::from_str(json).map_err(|_| CollateralError::InvalidJson)?;
+        let get_date = |key: &str| {
+            value
+                .get(key)
+                .and_then(Value::as_str)
+                .and_then(parse_utc_timestamp_s)
+                .ok_or_else(|| CollateralError::InvalidDate(format!("{}.{}", field, key)))
+        };
+        Ok(CollateralDates {
+            issue_date_s: get_date("issueDate")?,
+            next_update_s: get_date("nextUpdate")?,
+        })
+    }
+}
+
+/// Parses a UTC timestamp of the format `YYYY-MM-DDTHH:MM:SSZ`, as used in Intel's collateral,
+/// into seconds since the Unix epoch.
+fn parse_utc_timestamp_s(timestamp: &str) -> Option<u64> {
+    let bytes = timestamp.as_bytes();
+    if bytes.len() != 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || bytes[10] != b'T'
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+        || bytes[19] != b'Z'
+    {
+        return None;
+    }
+    let number = |start: usize, end: usize| -> Option<u64> {
+        let digits = &timestamp[start..end];
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse().ok()
+    };
+
+    let year = number(0, 4)?;
+    let month = number(5, 7)?;
+    let day = number(8, 10)?;
+    let hour = number(11, 13)?;
+    let minute = number(14, 16)?;
+    let second = number(17, 19)?;
+
+    let is_leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year => 29,
+        2 => 28,
+        _ => return None,
+    };
+    if year < 1970 || day == 0 || day > days_in_month || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    // Days since the Unix epoch, see http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some(days * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
 impl FromStr for Collateral {
     type Err = CollateralError;
 
@@ -104,4 +213,67 @@ pub enum CollateralError {
     },
     #[error("Invalid JSON format")]
     InvalidJson,
+    #[error("Missing or invalid date: {0}")]
+    InvalidDate(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLLATERAL: &str = include_str!("../../tests/samples/alice/quote_collateral.json");
+
+    #[test]
+    fn test_parse_utc_timestamp() {
+        assert_eq!(parse_utc_timestamp_s("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(
+            parse_utc_timestamp_s("2025-09-02T07:41:59Z"),
+            Some(1_756_798_919)
+        );
+        assert_eq!(
+            parse_utc_timestamp_s("2024-02-29T23:59:59Z"),
+            Some(1_709_251_199)
+        );
+
+        assert_eq!(parse_utc_timestamp_s("2025-02-29T00:00:00Z"), None);
+        assert_eq!(parse_utc_timestamp_s("2025-13-01T00:00:00Z"), None);
+        assert_eq!(parse_utc_timestamp_s("2025-09-02T24:00:00Z"), None);
+        assert_eq!(parse_utc_timestamp_s("2025-09-02 07:41:59Z"), None);
+        assert_eq!(parse_utc_timestamp_s("2025-09-02T07:41:59"), None);
+        assert_eq!(parse_utc_timestamp_s("+025-09-02T07:41:59Z"), None);
+        assert_eq!(parse_utc_timestamp_s("1969-12-31T23:59:59Z"), None);
+    }
+
+    #[test]
+    fn test_collateral_fmspc() {
+        let collateral = Collateral::from_str(COLLATERAL).unwrap();
+        assert_eq!(collateral.fmspc().unwrap(), "20a06f000000");
+    }
+
+    #[test]
+    fn test_collateral_dates() {
+        let collateral = Collateral::from_str(COLLATERAL).unwrap();
+
+        assert_eq!(
+            collateral.tcb_info_dates().unwrap(),
+            CollateralDates {
+                issue_date_s: 1_756_798_919,
+                next_update_s: 1_759_390_919,
+            }
+        );
+        assert_eq!(
+            collateral.qe_identity_dates().unwrap(),
+            CollateralDates {
+                issue_date_s: 1_756_797_003,
+                next_update_s: 1_759_389_003,
+            }
+        );
+        assert_eq!(
+            collateral.dates().unwrap(),
+            CollateralDates {
+                issue_date_s: 1_756_797_003,
+                next_update_s: 1_759_389_003,
+            }
+        );
+    }
 }
@@ -0,0 +1,233 @@
+use alloc::{string::String, vec::Vec};
+use dstack_sdk_types::dstack::EventLog;
+use k256::sha2::{Digest as _, Sha384};
+use thiserror::Error;
+
+// DSTACK_EVENT_TYPE is defined in https://github.com/Dstack-TEE/dstack/blob/cfa4cc4e8a4f525d537883b1a0ba5d9fbfd87f1e/tdx-attest/src/lib.rs#L28
+// It is the same for all events
+pub const DSTACK_EVENT_TYPE: u32 = 134217729;
+
+pub const RTMR3_INDEX: u32 = 3;
+
+/// Reason for an RTMR3 event log replay to fail. Indices refer to the position of the event in
+/// the full event log.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ReplayError {
+    #[error("event {index} has type {event_type}, expected {DSTACK_EVENT_TYPE}")]
+    UnexpectedEventType { index: usize, event_type: u32 },
+    #[error("event {index} has malformed digest {digest:?}")]
+    MalformedDigest { index: usize, digest: String },
+    #[error("event {index} has malformed payload {payload:?}")]
+    MalformedPayload { index: usize, payload: String },
+    #[error("event {index} has digest {logged}, recomputed {recomputed}")]
+    DigestMismatch {
+        index: usize,
+        logged: String,
+        recomputed: String,
+    },
+}
+
+impl ReplayError {
+    /// Index of the failed event in the event log.
+    pub fn index(&self) -> usize {
+        match self {
+            Self::UnexpectedEventType { index, .. }
+            | Self::MalformedDigest { index, .. }
+            | Self::MalformedPayload { index, .. }
+            | Self::DigestMismatch { index, .. } => *index,
+        }
+    }
+}
+
+/// An RTMR3 event which has been replayed successfully.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayedEvent {
+    /// Position of the event in the full event log.
+    pub index: usize,
+    pub event: String,
+    /// Digest recomputed from the event type, name and payload, which matches the logged digest.
+    pub digest: [u8; 48],
+    /// RTMR3 value after extending it with this event.
+    pub accumulator: [u8; 48],
+}
+
+/// Result of replaying the RTMR3 events of an event log. The replay stops at the first invalid
+/// event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rtmr3Replay {
+    pub events: Vec<ReplayedEvent>,
+    pub error: Option<ReplayError>,
+}
+
+impl Rtmr3Replay {
+    /// Replays RTMR3 from the event log by hashing all relevant events together, verifying the
+    /// digest of every event.
+    pub fn replay(event_log: &[EventLog]) -> Self {
+        let mut events = Vec::new();
+        let mut accumulator = [0u8; 48];
+
+        let rtmr3_events = event_log
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.imr == RTMR3_INDEX);
+
+        for (index, event) in rtmr3_events {
+            let digest = match Self::verify_event(index, event) {
+                Ok(digest) => digest,
+                Err(error) => {
+                    return Self {
+                        events,
+                        error: Some(error),
+                    }
+                }
+            };
+
+            let mut hasher = Sha384::new();
+            hasher.update(accumulator);
+            hasher.update(digest);
+            accumulator = hasher.finalize().into();
+            events.push(ReplayedEvent {
+                index,
+                event: event.event.clone(),
+                digest,
+                accumulator,
+            });
+        }
+
+        Self {
+            events,
+            error: None,
+        }
+    }
+
+    /// Final RTMR3 value, or `None` if the replay failed.
+    pub fn digest(&self) -> Option<[u8; 48]> {
+        if self.error.is_some() {
+            return None;
+        }
+        Some(
+            self.events
+                .last()
+                .map_or([0u8; 48], |event| event.accumulator),
+        )
+    }
+
+    /// Index of the first invalid event in the event log.
+    pub fn first_invalid_index(&self) -> Option<usize> {
+        self.error.as_ref().map(ReplayError::index)
+    }
+
+    /// Verifies the logged digest of the event and returns it.
+    fn verify_event(index: usize, event: &EventLog) -> Result<[u8; 48], ReplayError> {
+        // In Dstack, all events measured in RTMR3 are of type DSTACK_EVENT_TYPE
+        if event.event_type != DSTACK_EVENT_TYPE {
+            return Err(ReplayError::UnexpectedEventType {
+                index,
+                event_type: event.event_type,
+            });
+        }
+        let logged_digest: [u8; 48] = hex::decode(&event.digest)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| ReplayError::MalformedDigest {
+                index,
+                digest: event.digest.clone(),
+            })?;
+        let payload =
+            hex::decode(&event.event_payload).map_err(|_| ReplayError::MalformedPayload {
+                index,
+                payload: event.event_payload.clone(),
+            })?;
+
+        let digest = event_digest(event.event_type, &event.event, &payload);
+        if digest != logged_digest {
+            return Err(ReplayError::DigestMismatch {
+                index,
+                logged: event.digest.clone(),
+                recomputed: hex::encode(digest),
+            });
+        }
+        Ok(digest)
+    }
+}
+
+// Implementation taken to match Dstack's https://github.com/Dstack-TEE/dstack/blob/cfa4cc4e8a4f525d537883b1a0ba5d9fbfd87f1e/cc-eventlog/src/lib.rs#L54
+pub fn event_digest(event_type: u32, event: &str, payload: &[u8]) -> [u8; 48] {
+    let mut hasher = Sha384::new();
+    hasher.update(event_type.to_ne_bytes());
+    hasher.update(b":");
+    hasher.update(event.as_bytes());
+    hasher.update(b":");
+    hasher.update(payload);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dstack_sdk_types::dstack::TcbInfo;
+
+    const TCB_INFO: &str = include_str!("../../tests/samples/alice/tcb_info.json");
+
+    fn tcb_info() -> TcbInfo {
+        serde_json::from_str(TCB_INFO).unwrap()
+    }
+
+    fn first_rtmr3_index(tcb_info: &TcbInfo) -> usize {
+        tcb_info
+            .event_log
+            .iter()
+            .position(|e| e.imr == RTMR3_INDEX)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_replay() {
+        let tcb_info = tcb_info();
+        let replay = Rtmr3Replay::replay(&tcb_info.event_log);
+
+        assert_eq!(replay.error, None);
+        assert_eq!(replay.first_invalid_index(), None);
+        assert_eq!(
+            replay.events.len(),
+            tcb_info
+                .event_log
+                .iter()
+                .filter(|e| e.imr == RTMR3_INDEX)
+                .count()
+        );
+        assert_eq!(hex::encode(replay.digest().unwrap()), tcb_info.rtmr3);
+    }
+
+    #[test]
+    fn test_replay_malformed_digest() {
+        let mut tcb_info = tcb_info();
+        let index = first_rtmr3_index(&tcb_info) + 1;
+        tcb_info.event_log[index].digest = "not hex".into();
+
+        let replay = Rtmr3Replay::replay(&tcb_info.event_log);
+        assert_eq!(replay.digest(), None);
+        assert_eq!(replay.first_invalid_index(), Some(index));
+        assert_eq!(replay.events.len(), 1);
+        assert!(matches!(
+            replay.error,
+            Some(ReplayError::MalformedDigest { .. })
+        ));
+    }
+
+    #[test]
+    fn test_replay_digest_mismatch() {
+        let mut tcb_info = tcb_info();
+        let index = first_rtmr3_index(&tcb_info);
+        tcb_info.event_log[index].event_payload = "00".into();
+
+        let replay = Rtmr3Replay::replay(&tcb_info.event_log);
+        assert_eq!(replay.digest(), None);
+        assert_eq!(replay.first_invalid_index(), Some(index));
+        assert!(replay.events.is_empty());
+        assert!(matches!(
+            replay.error,
+            Some(ReplayError::DigestMismatch { .. })
+        ));
+    }
+}
@@ -110,6 +110,38 @@ pub struct Image;
     BorshDeserialize,
 )]
 pub struct Compose;
+#[cfg_attr(
+    all(feature = "abi", not(target_arch = "wasm32")),
+    derive(::schemars::JsonSchema),
+    derive(::borsh::BorshSchema)
+)]
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    BorshSerialize,
+    BorshDeserialize,
+)]
+pub struct Script;
+#[cfg_attr(
+    all(feature = "abi", not(target_arch = "wasm32")),
+    derive(::schemars::JsonSchema),
+    derive(::borsh::BorshSchema)
+)]
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    BorshSerialize,
+    BorshDeserialize,
+)]
+pub struct App;
 
 /// Hash of an MPC Docker image running in the TEE environment. Used as a proposal for a new TEE
 /// code hash to add to the whitelist, together with the TEE quote (which includes the RTMR3
@@ -121,6 +153,13 @@ pub type DockerImageHash = Hash32<Image>;
 /// image hash.
 pub type DockerComposeHash = Hash32<Compose>;
 
+/// Hash of the pre-launch script in the app compose, which is executed in the TEE environment
+/// before the Docker Compose file is launched.
+pub type PreLaunchScriptHash = Hash32<Script>;
+
+/// Hash of the full app compose JSON, which is measured into RTMR3 as the `compose-hash` event.
+pub type AppComposeHash = Hash32<App>;
+
 #[cfg(test)]
 mod tests {
     use super::*;
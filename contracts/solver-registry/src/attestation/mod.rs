@@ -1,7 +1,10 @@
 pub mod app_compose;
+pub mod app_compose_policy;
 pub mod attestation;
 pub mod collateral;
+pub mod event_log;
 pub mod hash;
 pub mod measurements;
 pub mod quote;
 pub mod report_data;
+pub mod tcb_policy;
@@ -1,4 +1,4 @@
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 use borsh::{BorshDeserialize, BorshSerialize};
 use derive_more::{Deref, From, Into};
 use serde::{Deserialize, Serialize};
@@ -26,3 +26,12 @@ use alloc::string::ToString;
     derive(borsh::BorshSchema)
 )]
 pub struct QuoteBytes(Vec<u8>);
+
+impl QuoteBytes {
+    /// Lowercase hex-encoded FMSPC of the platform which generated the quote, read from the PCK
+    /// certificate in the quote. Returns `None` if the quote can't be parsed.
+    pub fn fmspc(&self) -> Option<String> {
+        let quote = dcap_qvl::quote::Quote::parse(&self.0).ok()?;
+        quote.fmspc().ok().map(hex::encode)
+    }
+}
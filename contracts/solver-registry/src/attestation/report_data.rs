@@ -1,6 +1,6 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use derive_more::Constructor;
-use near_sdk::PublicKey;
+use near_sdk::{AccountId, PublicKey};
 use serde::{Deserialize, Serialize};
 use sha3::{Digest, Sha3_384};
 
@@ -25,6 +25,7 @@ const BINARY_VERSION_SIZE: usize = 2;
 )]
 pub enum ReportDataVersion {
     V1 = 1,
+    V2 = 2,
 }
 
 impl ReportDataVersion {
@@ -35,6 +36,7 @@ impl ReportDataVersion {
     pub fn from_be_bytes(bytes: [u8; BINARY_VERSION_SIZE]) -> Option<Self> {
         match u16::from_be_bytes(bytes) {
             1 => Some(Self::V1),
+            2 => Some(Self::V2),
             _ => None,
         }
     }
@@ -47,6 +49,8 @@ pub struct ReportDataV1 {
 
 /// report_data_v1: [u8; 64] =
 ///   [version(2 bytes big endian) || sha384(TLS pub key) || zero padding]
+///
+/// The curve type of the TLS public key is not committed, use [`ReportDataV2`] to bind it.
 impl ReportDataV1 {
     /// V1-specific format constants
     const PUBLIC_KEYS_OFFSET: usize = BINARY_VERSION_OFFSET + BINARY_VERSION_SIZE;
@@ -101,9 +105,92 @@ impl ReportDataV1 {
     }
 }
 
+/// Number of bytes for the registry-issued nonce in [`ReportDataV2`].
+pub const NONCE_SIZE: usize = 32;
+
+#[derive(Debug, Clone, Constructor)]
+pub struct ReportDataV2 {
+    signer_public_key: PublicKey,
+    intents_public_key: PublicKey,
+    worker_id: AccountId,
+    pool_id: u32,
+    registry_id: AccountId,
+    nonce: [u8; NONCE_SIZE],
+}
+
+/// report_data_v2: [u8; 64] =
+///   [version(2 bytes big endian) || sha384(binding) || zero padding]
+///
+/// binding =
+///   [len(signer pub key) || signer pub key || len(intents pub key) || intents pub key ||
+///    len(worker ID) || worker ID || pool ID || len(registry ID) || registry ID || nonce]
+///
+/// Public keys include the curve type. Lengths and the pool ID are 4 bytes big endian. The signer
+/// key is the worker's NEAR access key, while the intents key is used to sign intents. Unlike V1,
+/// the quote can't be replayed by another account, for another pool, on another registry
+/// deployment or at a later time.
+impl ReportDataV2 {
+    /// V2-specific format constants
+    const BINDING_HASH_OFFSET: usize = BINARY_VERSION_OFFSET + BINARY_VERSION_SIZE;
+    const BINDING_HASH_SIZE: usize = 48;
+
+    // Compile-time assertions for V2 format.
+    const _V2_LAYOUT_CHECK: () = {
+        assert!(
+            BINARY_VERSION_SIZE + Self::BINDING_HASH_SIZE <= REPORT_DATA_SIZE,
+            "V2: Version and binding hash must not exceed report data size."
+        );
+    };
+
+    /// Generates the binary representation of V2 report data.
+    pub fn to_bytes(&self) -> [u8; REPORT_DATA_SIZE] {
+        let mut report_data = [0u8; REPORT_DATA_SIZE];
+
+        // Copy binary version (2 bytes, big endian)
+        let version_bytes = ReportDataVersion::V2.to_be_bytes();
+        report_data[BINARY_VERSION_OFFSET..BINARY_VERSION_OFFSET + BINARY_VERSION_SIZE]
+            .copy_from_slice(&version_bytes);
+
+        // Generate and copy hash of the binding
+        let binding_hash = self.binding_hash();
+        report_data[Self::BINDING_HASH_OFFSET..Self::BINDING_HASH_OFFSET + Self::BINDING_HASH_SIZE]
+            .copy_from_slice(&binding_hash);
+
+        // Remaining bytes are already zero-padded by default
+        report_data
+    }
+
+    /// Parses V2 report data from bytes. Returns the hash of the binding.
+    pub fn from_bytes(bytes: &[u8; REPORT_DATA_SIZE]) -> [u8; Self::BINDING_HASH_SIZE] {
+        let mut hash = [0u8; Self::BINDING_HASH_SIZE];
+        hash.copy_from_slice(
+            &bytes[Self::BINDING_HASH_OFFSET..Self::BINDING_HASH_OFFSET + Self::BINDING_HASH_SIZE],
+        );
+        hash
+    }
+
+    /// Generates SHA3-384 hash of the public keys, worker, pool, registry and nonce.
+    fn binding_hash(&self) -> [u8; Self::BINDING_HASH_SIZE] {
+        fn update_with_len(hasher: &mut Sha3_384, data: &[u8]) {
+            hasher.update((data.len() as u32).to_be_bytes());
+            hasher.update(data);
+        }
+
+        let mut hasher = Sha3_384::new();
+        update_with_len(&mut hasher, self.signer_public_key.as_bytes());
+        update_with_len(&mut hasher, self.intents_public_key.as_bytes());
+        update_with_len(&mut hasher, self.worker_id.as_str().as_bytes());
+        hasher.update(self.pool_id.to_be_bytes());
+        update_with_len(&mut hasher, self.registry_id.as_str().as_bytes());
+        hasher.update(self.nonce);
+        hasher.finalize().into()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ReportData {
     V1(ReportDataV1),
+    V2(ReportDataV2),
 }
 
 impl ReportData {
@@ -114,6 +201,7 @@ impl ReportData {
     pub fn version(&self) -> ReportDataVersion {
         match self {
             ReportData::V1(_) => ReportDataVersion::V1,
+            ReportData::V2(_) => ReportDataVersion::V2,
         }
     }
 
@@ -121,6 +209,7 @@ impl ReportData {
     pub fn to_bytes(&self) -> [u8; REPORT_DATA_SIZE] {
         match self {
             ReportData::V1(v1) => v1.to_bytes(),
+            ReportData::V2(v2) => v2.to_bytes(),
         }
     }
 }
@@ -131,7 +220,7 @@ mod tests {
     use crate::attestation::report_data::ReportData;
     // use alloc::vec::Vec;
     // use dcap_qvl::quote::Quote;
-    use near_sdk::PublicKey;
+    use near_sdk::{CurveType, PublicKey};
     // use test_utils::attestation::{p2p_tls_key, quote};
 
     // #[test]
@@ -161,7 +250,13 @@ mod tests {
         let parsed = ReportDataVersion::from_be_bytes([0, 1]).unwrap();
         assert_eq!(parsed, ReportDataVersion::V1);
 
-        assert!(ReportDataVersion::from_be_bytes([0, 2]).is_none());
+        let version = ReportDataVersion::V2;
+        assert_eq!(version.to_be_bytes(), [0, 2]);
+
+        let parsed = ReportDataVersion::from_be_bytes([0, 2]).unwrap();
+        assert_eq!(parsed, ReportDataVersion::V2);
+
+        assert!(ReportDataVersion::from_be_bytes([0, 3]).is_none());
     }
 
     #[test]
@@ -173,6 +268,7 @@ mod tests {
             ReportData::V1(v1) => {
                 assert_eq!(&v1.tls_public_key, &tls_key);
             }
+            ReportData::V2(_) => panic!("Expected V1 report data"),
         }
 
         assert_eq!(data.version(), ReportDataVersion::V1);
@@ -247,4 +343,129 @@ mod tests {
         let bytes = ReportDataV1::new(tls_key);
         assert_eq!(bytes.to_bytes().len(), REPORT_DATA_SIZE);
     }
+
+    fn create_test_intents_key() -> PublicKey {
+        "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+            .parse()
+            .unwrap()
+    }
+
+    fn create_test_report_data_v2(nonce: [u8; NONCE_SIZE]) -> ReportDataV2 {
+        ReportDataV2::new(
+            create_test_key(),
+            create_test_intents_key(),
+            "alice.near".parse().unwrap(),
+            0,
+            "solver-registry.near".parse().unwrap(),
+            nonce,
+        )
+    }
+
+    #[test]
+    fn test_report_data_v2_layout() {
+        let report_data_v2 = create_test_report_data_v2([7u8; NONCE_SIZE]);
+        let bytes = report_data_v2.to_bytes();
+
+        let version_bytes =
+            &bytes[BINARY_VERSION_OFFSET..BINARY_VERSION_OFFSET + BINARY_VERSION_SIZE];
+        assert_eq!(version_bytes, &[0, 2]);
+
+        assert_eq!(
+            ReportDataV2::from_bytes(&bytes),
+            report_data_v2.binding_hash()
+        );
+
+        let padding = &bytes[ReportDataV2::BINDING_HASH_OFFSET + ReportDataV2::BINDING_HASH_SIZE..];
+        assert!(padding.iter().all(|&b| b == 0));
+
+        let report_data = ReportData::V2(report_data_v2);
+        assert_eq!(report_data.version(), ReportDataVersion::V2);
+        assert_eq!(report_data.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_report_data_v2_binding() {
+        let bytes = create_test_report_data_v2([7u8; NONCE_SIZE]).to_bytes();
+
+        // Different nonce
+        assert_ne!(
+            create_test_report_data_v2([8u8; NONCE_SIZE]).to_bytes(),
+            bytes
+        );
+
+        // Different keys
+        let other_intents_key = ReportDataV2::new(
+            create_test_key(),
+            create_test_key(),
+            "alice.near".parse().unwrap(),
+            0,
+            "solver-registry.near".parse().unwrap(),
+            [7u8; NONCE_SIZE],
+        );
+        assert_ne!(other_intents_key.to_bytes(), bytes);
+
+        let swapped_keys = ReportDataV2::new(
+            create_test_intents_key(),
+            create_test_key(),
+            "alice.near".parse().unwrap(),
+            0,
+            "solver-registry.near".parse().unwrap(),
+            [7u8; NONCE_SIZE],
+        );
+        assert_ne!(swapped_keys.to_bytes(), bytes);
+
+        // Different worker, pool and registry
+        let other_worker = ReportDataV2::new(
+            create_test_key(),
+            create_test_intents_key(),
+            "bob.near".parse().unwrap(),
+            0,
+            "solver-registry.near".parse().unwrap(),
+            [7u8; NONCE_SIZE],
+        );
+        assert_ne!(other_worker.to_bytes(), bytes);
+
+        let other_pool = ReportDataV2::new(
+            create_test_key(),
+            create_test_intents_key(),
+            "alice.near".parse().unwrap(),
+            1,
+            "solver-registry.near".parse().unwrap(),
+            [7u8; NONCE_SIZE],
+        );
+        assert_ne!(other_pool.to_bytes(), bytes);
+
+        let other_registry = ReportDataV2::new(
+            create_test_key(),
+            create_test_intents_key(),
+            "alice.near".parse().unwrap(),
+            0,
+            "solver-registry.testnet".parse().unwrap(),
+            [7u8; NONCE_SIZE],
+        );
+        assert_ne!(other_registry.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_report_data_v2_key_types() {
+        // Keys of both curve types with the same payload prefix
+        let ed25519_key = PublicKey::from_parts(CurveType::ED25519, vec![1u8; 32]).unwrap();
+        let secp256k1_key = PublicKey::from_parts(CurveType::SECP256K1, vec![1u8; 64]).unwrap();
+
+        let report_data_v2 = |intents_public_key: PublicKey| {
+            ReportDataV2::new(
+                create_test_key(),
+                intents_public_key,
+                "alice.near".parse().unwrap(),
+                0,
+                "solver-registry.near".parse().unwrap(),
+                [7u8; NONCE_SIZE],
+            )
+        };
+        let ed25519_bytes = report_data_v2(ed25519_key).to_bytes();
+        let secp256k1_bytes = report_data_v2(secp256k1_key).to_bytes();
+        assert_eq!(&ed25519_bytes[..BINARY_VERSION_SIZE], &[0, 2]);
+        assert_eq!(&secp256k1_bytes[..BINARY_VERSION_SIZE], &[0, 2]);
+        assert_ne!(ed25519_bytes, secp256k1_bytes);
+    }
 }
@@ -0,0 +1,116 @@
+use near_sdk::near;
+
+use crate::types::TimestampMs;
+
+/// The "UpToDate" TCB status indicates that the measured platform components (CPU microcode,
+/// firmware, etc.) match the latest known good values published by Intel and do not require any
+/// updates or mitigations.
+pub const TCB_STATUS_UP_TO_DATE: &str = "UpToDate";
+
+/// A security advisory which is tolerated in a verified quote, optionally until a sunset date.
+#[near(serializers = [json, borsh])]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdvisoryException {
+    /// Intel security advisory ID, e.g. `INTEL-SA-00837`.
+    pub advisory_id: String,
+    /// The advisory is no longer tolerated from this timestamp on. `None` means no sunset date.
+    pub expires_at_ms: Option<TimestampMs>,
+}
+
+/// Policy deciding which TCB statuses and security advisories are accepted for a TEE quote.
+#[near(serializers = [json, borsh])]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TcbPolicy {
+    /// Accepted TCB statuses, e.g. `UpToDate` or `SWHardeningNeeded`.
+    pub accepted_statuses: Vec<String>,
+    /// Advisory IDs which may be reported for a quote without failing the verification.
+    pub advisory_exceptions: Vec<AdvisoryException>,
+}
+
+impl Default for TcbPolicy {
+    /// Only accept up-to-date platforms without any outstanding security advisory.
+    fn default() -> Self {
+        Self {
+            accepted_statuses: vec![TCB_STATUS_UP_TO_DATE.to_string()],
+            advisory_exceptions: vec![],
+        }
+    }
+}
+
+impl TcbPolicy {
+    /// Checks whether a quote with the given TCB status and advisory IDs is acceptable at the
+    /// given time.
+    pub fn accepts(
+        &self,
+        status: &str,
+        advisory_ids: &[String],
+        timestamp_ms: TimestampMs,
+    ) -> bool {
+        self.accepted_statuses.iter().any(|s| s == status)
+            && advisory_ids
+                .iter()
+                .all(|advisory_id| self.is_advisory_tolerated(advisory_id, timestamp_ms))
+    }
+
+    fn is_advisory_tolerated(&self, advisory_id: &str, timestamp_ms: TimestampMs) -> bool {
+        self.advisory_exceptions.iter().any(|exception| {
+            exception.advisory_id == advisory_id
+                && exception
+                    .expires_at_ms
+                    .is_none_or(|expires_at_ms| timestamp_ms < expires_at_ms)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADVISORY_ID: &str = "INTEL-SA-00837";
+
+    fn policy_with_exception(expires_at_ms: Option<TimestampMs>) -> TcbPolicy {
+        TcbPolicy {
+            accepted_statuses: vec![
+                TCB_STATUS_UP_TO_DATE.to_string(),
+                "SWHardeningNeeded".to_string(),
+            ],
+            advisory_exceptions: vec![AdvisoryException {
+                advisory_id: ADVISORY_ID.to_string(),
+                expires_at_ms,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_default_policy() {
+        let policy = TcbPolicy::default();
+        assert!(policy.accepts(TCB_STATUS_UP_TO_DATE, &[], 0));
+        assert!(!policy.accepts("SWHardeningNeeded", &[], 0));
+        assert!(!policy.accepts(TCB_STATUS_UP_TO_DATE, &[ADVISORY_ID.to_string()], 0));
+    }
+
+    #[test]
+    fn test_accepted_statuses() {
+        let policy = policy_with_exception(None);
+        assert!(policy.accepts("SWHardeningNeeded", &[], 0));
+        assert!(!policy.accepts("OutOfDate", &[], 0));
+    }
+
+    #[test]
+    fn test_advisory_exceptions() {
+        let policy = policy_with_exception(None);
+        assert!(policy.accepts("SWHardeningNeeded", &[ADVISORY_ID.to_string()], 0));
+        assert!(!policy.accepts(
+            "SWHardeningNeeded",
+            &[ADVISORY_ID.to_string(), "INTEL-SA-00828".to_string()],
+            0
+        ));
+    }
+
+    #[test]
+    fn test_advisory_exception_sunset() {
+        let policy = policy_with_exception(Some(1_000));
+        assert!(policy.accepts(TCB_STATUS_UP_TO_DATE, &[ADVISORY_ID.to_string()], 999));
+        assert!(!policy.accepts(TCB_STATUS_UP_TO_DATE, &[ADVISORY_ID.to_string()], 1_000));
+    }
+}
@@ -0,0 +1,385 @@
+//! Offline verifier of worker attestations. Runs the same verification pipeline as
+//! `register_worker` on captured quote, collateral and TCB info files, and prints the result of
+//! each stage with the expected and the measured values.
+//!
+//! The `compose-hash` subcommand computes the hashes of an app compose for `approve_compose_hash`,
+//! and the `event-log` subcommand replays the RTMR3 event log of a TCB info.
+
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use dstack_sdk_types::dstack::TcbInfo;
+use near_sdk::{AccountId, PublicKey};
+use solver_registry::attestation::{
+    app_compose::{AppCompose, DockerComposeString},
+    app_compose_policy::AppComposePolicy,
+    attestation::{Attestation, AttestationPolicy, DstackAttestation},
+    collateral::Collateral,
+    event_log::Rtmr3Replay,
+    hash::{DockerComposeHash, DockerImageHash, PreLaunchScriptHash},
+    quote::QuoteBytes,
+    report_data::{ReportData, ReportDataV2, NONCE_SIZE},
+    tcb_policy::TcbPolicy,
+};
+
+const DEFAULT_MAX_COLLATERAL_AGE_S: u64 = 30 * 24 * 60 * 60;
+
+const USAGE: &str = "\
+Usage: solver-registry-verify --quote <FILE> --collateral <FILE> --tcb-info <FILE> --public-key <KEY> [OPTIONS]
+       solver-registry-verify compose-hash <FILE>
+       solver-registry-verify event-log <TCB_INFO_FILE>
+
+Verifies a worker attestation with the same pipeline as `register_worker`.
+
+Required:
+  --quote <FILE>                   File with the hex-encoded quote
+  --collateral <FILE>              Collateral JSON file
+  --tcb-info <FILE>                TCB info JSON file
+  --public-key <KEY>               Public key committed in the report data, e.g. ed25519:...
+
+Options:
+  --timestamp <SECONDS>            Verification time, defaults to now
+  --max-collateral-age <SECONDS>   Maximum age of the collateral, defaults to 30 days
+  --compose-hash <HEX>             Approved docker compose hash (repeatable)
+  --pre-launch-script-hash <HEX>   Approved pre-launch script hash (repeatable)
+  --docker-image-hash <HEX>        Approved docker image hash (repeatable)
+  --docker-image-hash-event <NAME> RTMR3 event of the docker image hash, not verified if not set
+  --tcb-policy <FILE>              TCB policy JSON file, defaults to UpToDate only
+  --app-compose-policy <FILE>      App compose policy JSON file, defaults to the registry's default
+
+Report data V2 (all required if any is set):
+  --nonce <HEX>                    Registration challenge nonce
+  --intents-public-key <KEY>       Intents public key
+  --worker-id <ACCOUNT_ID>         Worker account ID
+  --pool-id <ID>                   Pool ID
+  --registry-id <ACCOUNT_ID>       Solver registry contract ID
+
+Compose hash:
+  compose-hash <FILE>              Prints the docker compose hash to approve with `approve_compose_hash`
+                                   and the app compose hash measured into RTMR3. FILE is an app compose
+                                   JSON, a TCB info JSON or a docker compose YAML (docker compose hash only)
+
+Event log:
+  event-log <TCB_INFO_FILE>        Replays the RTMR3 event log and prints the recomputed digest and the
+                                   running RTMR3 value of every event, up to the first invalid event
+";
+
+#[derive(Default)]
+struct Args {
+    quote: Option<String>,
+    collateral: Option<String>,
+    tcb_info: Option<String>,
+    public_key: Option<String>,
+    timestamp: Option<String>,
+    max_collateral_age: Option<String>,
+    compose_hashes: Vec<String>,
+    pre_launch_script_hashes: Vec<String>,
+    docker_image_hashes: Vec<String>,
+    docker_image_hash_event: Option<String>,
+    tcb_policy: Option<String>,
+    app_compose_policy: Option<String>,
+    nonce: Option<String>,
+    intents_public_key: Option<String>,
+    worker_id: Option<String>,
+    pool_id: Option<String>,
+    registry_id: Option<String>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        while let Some(flag) = args.next() {
+            if flag == "-h" || flag == "--help" {
+                return Err(String::new());
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", flag))?;
+            match flag.as_str() {
+                "--quote" => parsed.quote = Some(value),
+                "--collateral" => parsed.collateral = Some(value),
+                "--tcb-info" => parsed.tcb_info = Some(value),
+                "--public-key" => parsed.public_key = Some(value),
+                "--timestamp" => parsed.timestamp = Some(value),
+                "--max-collateral-age" => parsed.max_collateral_age = Some(value),
+                "--compose-hash" => parsed.compose_hashes.push(value),
+                "--pre-launch-script-hash" => parsed.pre_launch_script_hashes.push(value),
+                "--docker-image-hash" => parsed.docker_image_hashes.push(value),
+                "--docker-image-hash-event" => parsed.docker_image_hash_event = Some(value),
+                "--tcb-policy" => parsed.tcb_policy = Some(value),
+                "--app-compose-policy" => parsed.app_compose_policy = Some(value),
+                "--nonce" => parsed.nonce = Some(value),
+                "--intents-public-key" => parsed.intents_public_key = Some(value),
+                "--worker-id" => parsed.worker_id = Some(value),
+                "--pool-id" => parsed.pool_id = Some(value),
+                "--registry-id" => parsed.registry_id = Some(value),
+                _ => return Err(format!("Unknown argument: {}", flag)),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+fn required<'a>(value: &'a Option<String>, flag: &str) -> Result<&'a str, String> {
+    value
+        .as_deref()
+        .ok_or_else(|| format!("Missing required argument {}", flag))
+}
+
+fn read_file(path: &str) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))
+}
+
+fn parse_public_key(value: &str) -> Result<PublicKey, String> {
+    value
+        .parse()
+        .map_err(|err| format!("Invalid public key {}: {:?}", value, err))
+}
+
+fn parse_account_id(value: &str) -> Result<AccountId, String> {
+    value
+        .parse()
+        .map_err(|err| format!("Invalid account ID {}: {}", value, err))
+}
+
+fn parse_hashes<T>(
+    values: &[String],
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<Vec<T>, String> {
+    values.iter().map(|value| parse(value)).collect()
+}
+
+fn expected_report_data(args: &Args, public_key: PublicKey) -> Result<ReportData, String> {
+    let Some(nonce) = args.nonce.as_deref() else {
+        return Ok(ReportData::new(public_key));
+    };
+    let nonce: [u8; NONCE_SIZE] = hex::decode(nonce)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| format!("Invalid nonce {}", nonce))?;
+    let pool_id = required(&args.pool_id, "--pool-id")?;
+    Ok(ReportData::V2(ReportDataV2::new(
+        public_key,
+        parse_public_key(required(&args.intents_public_key, "--intents-public-key")?)?,
+        parse_account_id(required(&args.worker_id, "--worker-id")?)?,
+        pool_id
+            .parse()
+            .map_err(|_| format!("Invalid pool ID {}", pool_id))?,
+        parse_account_id(required(&args.registry_id, "--registry-id")?)?,
+        nonce,
+    )))
+}
+
+fn run(args: Args) -> Result<bool, String> {
+    let quote_hex = read_file(required(&args.quote, "--quote")?)?;
+    let quote = QuoteBytes::from(
+        hex::decode(quote_hex.trim()).map_err(|err| format!("Invalid quote hex: {}", err))?,
+    );
+    let collateral = Collateral::from_str(&read_file(required(&args.collateral, "--collateral")?)?)
+        .map_err(|err| format!("Invalid collateral: {:?}", err))?;
+    let tcb_info: TcbInfo =
+        serde_json::from_str(&read_file(required(&args.tcb_info, "--tcb-info")?)?)
+            .map_err(|err| format!("Invalid TCB info: {}", err))?;
+    let public_key = parse_public_key(required(&args.public_key, "--public-key")?)?;
+    let expected_report_data = expected_report_data(&args, public_key)?;
+
+    let timestamp_s = match args.timestamp.as_deref() {
+        Some(timestamp) => timestamp
+            .parse()
+            .map_err(|_| format!("Invalid timestamp {}", timestamp))?,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| err.to_string())?
+            .as_secs(),
+    };
+    let max_collateral_age_s = match args.max_collateral_age.as_deref() {
+        Some(max_age) => max_age
+            .parse()
+            .map_err(|_| format!("Invalid max collateral age {}", max_age))?,
+        None => DEFAULT_MAX_COLLATERAL_AGE_S,
+    };
+
+    let tcb_policy: TcbPolicy = match args.tcb_policy.as_deref() {
+        Some(path) => serde_json::from_str(&read_file(path)?)
+            .map_err(|err| format!("Invalid TCB policy: {}", err))?,
+        None => TcbPolicy::default(),
+    };
+    let app_compose_policy: AppComposePolicy = match args.app_compose_policy.as_deref() {
+        Some(path) => serde_json::from_str(&read_file(path)?)
+            .map_err(|err| format!("Invalid app compose policy: {}", err))?,
+        None => AppComposePolicy::default(),
+    };
+    let allowed_docker_compose_hashes = parse_hashes(&args.compose_hashes, |hash| {
+        DockerComposeHash::try_from_hex(hash).map_err(|err| err.to_string())
+    })?;
+    let allowed_pre_launch_script_hashes = parse_hashes(&args.pre_launch_script_hashes, |hash| {
+        PreLaunchScriptHash::try_from_hex(hash).map_err(|err| err.to_string())
+    })?;
+    let allowed_docker_image_hashes = parse_hashes(&args.docker_image_hashes, |hash| {
+        DockerImageHash::try_from_hex(hash).map_err(|err| err.to_string())
+    })?;
+
+    let attestation = Attestation::Dstack(DstackAttestation::new(quote, collateral, tcb_info));
+    let report = attestation.verification_report(
+        expected_report_data,
+        timestamp_s,
+        &AttestationPolicy {
+            tcb_policy: &tcb_policy,
+            app_compose_policy: &app_compose_policy,
+            docker_image_hash_event: args.docker_image_hash_event.as_deref(),
+            allowed_docker_image_hashes: &allowed_docker_image_hashes,
+            allowed_docker_compose_hashes: &allowed_docker_compose_hashes,
+            allowed_pre_launch_script_hashes: &allowed_pre_launch_script_hashes,
+            max_collateral_age_s,
+        },
+    );
+
+    for stage in &report.stages {
+        println!(
+            "[{}] {}",
+            if stage.passed { "PASS" } else { "FAIL" },
+            stage.stage
+        );
+        println!("    expected: {}", stage.expected);
+        println!("    measured: {}", stage.measured);
+    }
+    if let Some(tcb_status) = report.tcb_status.as_deref() {
+        println!("TCB status: {}", tcb_status);
+    }
+    if let Some(valid_until_s) = report.collateral_valid_until_s {
+        println!("Collateral valid until: {}", valid_until_s);
+    }
+    println!(
+        "Attestation verification {}",
+        if report.passed() { "passed" } else { "failed" }
+    );
+
+    Ok(report.passed())
+}
+
+/// Prints the hashes of an app compose JSON, the app compose of a TCB info JSON, or a docker
+/// compose YAML file.
+fn run_compose_hash(path: &str) -> Result<(), String> {
+    let content = read_file(path)?;
+
+    let app_compose_json = match serde_json::from_str::<serde_json::Value>(&content) {
+        // TCB info JSON with the app compose as a string
+        Ok(serde_json::Value::Object(object)) if object.contains_key("app_compose") => Some(
+            object["app_compose"]
+                .as_str()
+                .ok_or("Invalid app_compose in TCB info")?
+                .to_string(),
+        ),
+        Ok(_) => Some(content),
+        // Not a JSON, so it's a docker compose YAML
+        Err(_) => None,
+    };
+
+    match app_compose_json {
+        Some(app_compose_json) => {
+            let app_compose: AppCompose = serde_json::from_str(&app_compose_json)
+                .map_err(|err| format!("Invalid app compose: {}", err))?;
+            println!(
+                "Docker compose hash (approve_compose_hash): {}",
+                app_compose.docker_compose_hash().as_hex()
+            );
+            println!(
+                "App compose hash (compose-hash event in RTMR3): {}",
+                AppCompose::app_compose_hash(&app_compose_json).as_hex()
+            );
+        }
+        None => {
+            let docker_compose = DockerComposeString::from(content);
+            println!(
+                "Docker compose hash (approve_compose_hash): {}",
+                docker_compose.compose_hash().as_hex()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Replays the RTMR3 event log of a TCB info JSON. Returns whether the replay matches the RTMR3
+/// of the TCB info.
+fn run_event_log(path: &str) -> Result<bool, String> {
+    let tcb_info: TcbInfo = serde_json::from_str(&read_file(path)?)
+        .map_err(|err| format!("Invalid TCB info: {}", err))?;
+
+    let replay = Rtmr3Replay::replay(&tcb_info.event_log);
+    for event in &replay.events {
+        println!("[{}] {}", event.index, event.event);
+        println!("    digest: {}", hex::encode(event.digest));
+        println!("    rtmr3:  {}", hex::encode(event.accumulator));
+    }
+
+    let passed = match (replay.digest(), replay.error.as_ref()) {
+        (Some(digest), _) => {
+            println!("Replayed RTMR3: {}", hex::encode(digest));
+            println!("Expected RTMR3: {}", tcb_info.rtmr3);
+            hex::encode(digest) == tcb_info.rtmr3
+        }
+        (None, Some(error)) => {
+            println!("Invalid event log: {}", error);
+            false
+        }
+        (None, None) => false,
+    };
+    println!(
+        "Event log replay {}",
+        if passed { "passed" } else { "failed" }
+    );
+    Ok(passed)
+}
+
+/// Runs a subcommand with a single file argument.
+fn run_file_subcommand(
+    mut cli_args: impl Iterator<Item = String>,
+    run: impl FnOnce(&str) -> Result<bool, String>,
+) -> ExitCode {
+    let (Some(path), None) = (cli_args.next(), cli_args.next()) else {
+        eprint!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+    match run(&path) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut cli_args = std::env::args().skip(1).peekable();
+    match cli_args.peek().map(String::as_str) {
+        Some("compose-hash") => {
+            return run_file_subcommand(cli_args.skip(1), |path| {
+                run_compose_hash(path).map(|()| true)
+            })
+        }
+        Some("event-log") => return run_file_subcommand(cli_args.skip(1), run_event_log),
+        _ => {}
+    }
+
+    let args = match Args::parse(cli_args) {
+        Ok(args) => args,
+        Err(err) => {
+            if !err.is_empty() {
+                eprintln!("{}\n", err);
+            }
+            eprint!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::from(2)
+        }
+    }
+}
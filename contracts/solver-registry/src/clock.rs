@@ -0,0 +1,30 @@
+use crate::*;
+
+/// Storage key of the offset of the attestation clock from the block time, kept outside of the
+/// contract state so that the state layout doesn't depend on the `test-clock` feature
+#[cfg(feature = "test-clock")]
+const ATTESTATION_CLOCK_OFFSET_KEY: &[u8] = b"ATTESTATION_CLOCK_OFFSET";
+
+#[cfg(feature = "test-clock")]
+#[near]
+impl Contract {
+    /// Set the attestation clock to `timestamp_ms`, from which it advances with the block time
+    /// (owner only). Lets sandbox tests verify the sample attestations after their collateral
+    /// expired. Only built with the `test-clock` feature, which must never be deployed.
+    pub fn set_attestation_timestamp_ms(&mut self, timestamp_ms: TimestampMs) {
+        self.assert_owner();
+        let offset_ms = block_timestamp_ms().saturating_sub(timestamp_ms);
+        env::storage_write(ATTESTATION_CLOCK_OFFSET_KEY, &offset_ms.to_le_bytes());
+    }
+}
+
+/// Time against which attestations and their collaterals are verified. It's the block time,
+/// unless the attestation clock is set with the `test-clock` feature.
+pub(crate) fn attestation_timestamp_ms() -> TimestampMs {
+    #[cfg(feature = "test-clock")]
+    if let Some(offset_ms) = env::storage_read(ATTESTATION_CLOCK_OFFSET_KEY) {
+        let offset_ms = u64::from_le_bytes(offset_ms.try_into().expect("Invalid clock offset"));
+        return block_timestamp_ms().saturating_sub(offset_ms);
+    }
+    block_timestamp_ms()
+}
@@ -0,0 +1,121 @@
+use near_sdk::near;
+
+use crate::*;
+
+#[near]
+impl Contract {
+    /// Cache the collateral for the FMSPC of a quote. Anyone can submit collateral, which must
+    /// verify the quote with the signatures chaining up to Intel's root CA at the current time.
+    /// The collateral replaces the cached one only if it's newer.
+    ///
+    /// The attached deposit must cover the storage of the collateral. The unused deposit is
+    /// refunded.
+    #[payable]
+    pub fn submit_collateral(&mut self, quote_hex: String, collateral: String) {
+        let quote_bytes = QuoteBytes::from(decode(&quote_hex).expect("Invalid quote hex"));
+        let collateral = Collateral::from_str(&collateral).expect("Invalid collateral format");
+
+        let timestamp_s = attestation_timestamp_ms() / 1000;
+        if let Err(err) = dcap_qvl::verify::verify(&quote_bytes, &collateral, timestamp_s) {
+            env::panic_str(&format!("Collateral verification failed: {:?}", err));
+        }
+
+        let initial_storage_usage = env::storage_usage();
+        self.cache_collateral(collateral);
+
+        let storage_cost = env::storage_byte_cost()
+            .saturating_mul(env::storage_usage().saturating_sub(initial_storage_usage) as u128);
+        let attached_deposit = env::attached_deposit();
+        require!(
+            attached_deposit >= storage_cost,
+            format!(
+                "Not enough attached deposit, {} required for storage",
+                storage_cost.exact_amount_display()
+            )
+        );
+        let refund = attached_deposit.saturating_sub(storage_cost);
+        if !refund.is_zero() {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+    }
+
+    /// Cache the collateral for its FMSPC without a quote (owner only)
+    pub fn set_collateral(&mut self, collateral: String) {
+        self.assert_owner();
+        let collateral = Collateral::from_str(&collateral).expect("Invalid collateral format");
+
+        self.cache_collateral(collateral);
+    }
+
+    /// Remove the cached collateral of the FMSPC (owner only)
+    pub fn remove_collateral(&mut self, fmspc: String) {
+        self.assert_owner();
+        let fmspc = fmspc.to_ascii_lowercase();
+        require!(
+            self.cached_collaterals.remove(&fmspc).is_some(),
+            "Collateral not found"
+        );
+
+        Event::CollateralRemoved { fmspc: &fmspc }.emit();
+    }
+}
+
+impl Contract {
+    /// Stores the collateral for its FMSPC if it's newer than the cached one.
+    fn cache_collateral(&mut self, collateral: Collateral) {
+        let fmspc = collateral.fmspc().expect("Invalid collateral FMSPC");
+        let dates = collateral.dates().expect("Invalid collateral dates");
+        if let Some(cached_collateral) = self.cached_collaterals.get(&fmspc) {
+            let cached_dates = cached_collateral
+                .dates()
+                .expect("Invalid cached collateral dates");
+            require!(
+                dates.issue_date_s > cached_dates.issue_date_s,
+                "Collateral is not newer than the cached one"
+            );
+        }
+
+        self.cached_collaterals.insert(fmspc.clone(), collateral);
+
+        Event::CollateralCached {
+            fmspc: &fmspc,
+            issue_date_ms: &dates.issue_date_s.saturating_mul(1000),
+            next_update_ms: &dates.next_update_s.saturating_mul(1000),
+            submitter_id: &env::predecessor_account_id(),
+        }
+        .emit();
+    }
+
+    /// Returns the cached collateral for the FMSPC of the quote, which takes precedence over the
+    /// worker's collateral while it's fresh at `timestamp_s`. Falls back to the worker's
+    /// collateral if none is cached or the cached one is stale.
+    pub(crate) fn resolve_collateral(
+        &self,
+        quote_bytes: &QuoteBytes,
+        collateral: Option<&str>,
+        timestamp_s: u64,
+    ) -> Collateral {
+        let cached_collateral = quote_bytes
+            .fmspc()
+            .and_then(|fmspc| self.cached_collaterals.get(&fmspc));
+        let is_fresh = |collateral: &Collateral| {
+            collateral.dates().is_ok_and(|dates| {
+                Attestation::verify_collateral_freshness(
+                    &dates,
+                    timestamp_s,
+                    self.max_collateral_age_ms / 1000,
+                )
+            })
+        };
+        match (cached_collateral, collateral) {
+            (Some(cached_collateral), None) => cached_collateral.clone(),
+            (Some(cached_collateral), Some(_)) if is_fresh(cached_collateral) => {
+                cached_collateral.clone()
+            }
+            (_, Some(collateral)) => {
+                Collateral::from_str(collateral).expect("Invalid collateral format")
+            }
+            (None, None) => env::panic_str("Collateral not cached for the quote's FMSPC"),
+        }
+    }
+}
@@ -1,8 +1,12 @@
 use near_sdk::serde::Serialize;
 use near_sdk::serde_json::json;
-use near_sdk::{log, AccountId, PublicKey};
+use near_sdk::{json_types::Base58CryptoHash, log, AccountId, PublicKey};
 
-use crate::types::TimestampMs;
+use crate::attestation::{app_compose_policy::AppComposePolicy, tcb_policy::TcbPolicy};
+use crate::types::{
+    ActionId, GuardianSet, KeyType, PausableFeature, Role, SourceMetadata, TimelockAction,
+    TimestampMs,
+};
 
 pub const EVENT_STANDARD: &str = "solver-registry";
 pub const EVENT_STANDARD_VERSION: &str = "1.0.0";
@@ -22,6 +26,7 @@ pub enum Event<'a> {
         public_key: &'a PublicKey,
         compose_hash: &'a String,
         checksum: &'a String,
+        tcb_status: &'a String,
     },
     WorkerRemoved {
         worker_id: &'a AccountId,
@@ -30,6 +35,32 @@ pub enum Event<'a> {
         compose_hash: &'a String,
         checksum: &'a String,
     },
+    WorkerKeyRotated {
+        worker_id: &'a AccountId,
+        pool_id: &'a u32,
+        old_public_key: &'a PublicKey,
+        new_public_key: &'a PublicKey,
+    },
+    WorkerKeyOrphaned {
+        pool_id: &'a u32,
+        public_key: &'a PublicKey,
+    },
+    OrphanedWorkerKeyRevoked {
+        pool_id: &'a u32,
+        public_key: &'a PublicKey,
+    },
+    WorkerKeyRevocationFailed {
+        pool_id: &'a u32,
+        public_key: &'a PublicKey,
+    },
+    WorkerAttestationRefreshed {
+        worker_id: &'a AccountId,
+        pool_id: &'a u32,
+        compose_hash: &'a String,
+        checksum: &'a String,
+        tcb_status: &'a String,
+        attestation_valid_until_ms: &'a TimestampMs,
+    },
     CreateLiquidityPool {
         pool_id: &'a u32,
         token_ids: &'a Vec<AccountId>,
@@ -40,16 +71,156 @@ pub enum Event<'a> {
         worker_id: &'a AccountId,
         timestamp_ms: &'a TimestampMs,
     },
+    RegistrationChallengeIssued {
+        worker_id: &'a AccountId,
+        pool_id: &'a u32,
+        nonce: &'a String,
+        expires_at_ms: &'a TimestampMs,
+    },
+    RegistrationChallengeTtlUpdated {
+        registration_challenge_ttl_ms: &'a TimestampMs,
+    },
+    LegacyReportDataUpdated {
+        allow_legacy_report_data: &'a bool,
+    },
+    AllowedKeyTypesUpdated {
+        allowed_key_types: &'a Vec<KeyType>,
+    },
+    MaxCollateralAgeUpdated {
+        max_collateral_age_ms: &'a TimestampMs,
+    },
+    CollateralCached {
+        fmspc: &'a String,
+        issue_date_ms: &'a TimestampMs,
+        next_update_ms: &'a TimestampMs,
+        submitter_id: &'a AccountId,
+    },
+    CollateralRemoved {
+        fmspc: &'a String,
+    },
     ComposeHashApproved {
         compose_hash: &'a String,
     },
     ComposeHashRemoved {
         compose_hash: &'a String,
     },
+    PreLaunchScriptApproved {
+        compose_hash: &'a String,
+        pre_launch_script_hash: &'a String,
+    },
+    PreLaunchScriptRemoved {
+        compose_hash: &'a String,
+        pre_launch_script_hash: &'a String,
+    },
+    DockerImageHashApproved {
+        compose_hash: &'a String,
+        docker_image_hash: &'a String,
+    },
+    DockerImageHashRemoved {
+        compose_hash: &'a String,
+        docker_image_hash: &'a String,
+    },
+    DockerImageHashEventUpdated {
+        docker_image_hash_event: &'a Option<String>,
+    },
+    ComposeHashAllowedEnvsUpdated {
+        compose_hash: &'a String,
+        allowed_envs: &'a Option<Vec<String>>,
+    },
+    ActionScheduled {
+        action_id: &'a ActionId,
+        action: &'a TimelockAction,
+        executable_at_ms: &'a TimestampMs,
+    },
+    ActionExecuted {
+        action_id: &'a ActionId,
+        action: &'a TimelockAction,
+    },
+    ActionCancelled {
+        action_id: &'a ActionId,
+        action: &'a TimelockAction,
+    },
+    TimelockDelayUpdated {
+        timelock_delay_ms: &'a TimestampMs,
+    },
+    GuardiansUpdated {
+        guardian_set: &'a Option<GuardianSet>,
+    },
+    ComposeHashVoted {
+        guardian_id: &'a AccountId,
+        compose_hash: &'a String,
+        approve: &'a bool,
+        votes: &'a u32,
+        threshold: &'a u32,
+    },
+    ActionVoted {
+        guardian_id: &'a AccountId,
+        action: &'a TimelockAction,
+        votes: &'a u32,
+        threshold: &'a u32,
+    },
+    Paused {
+        features: &'a Vec<PausableFeature>,
+    },
+    Unpaused {
+        features: &'a Vec<PausableFeature>,
+    },
+    RoleGranted {
+        role: &'a Role,
+        account_id: &'a AccountId,
+    },
+    RoleRevoked {
+        role: &'a Role,
+        account_id: &'a AccountId,
+    },
+    OwnershipTransferProposed {
+        owner_id: &'a AccountId,
+        pending_owner_id: &'a AccountId,
+    },
+    OwnershipProposalCancelled {
+        owner_id: &'a AccountId,
+        pending_owner_id: &'a AccountId,
+    },
+    IntentsVaultCodePublished {
+        code_hash: &'a Base58CryptoHash,
+    },
+    PoolVaultUpgraded {
+        pool_id: &'a u32,
+        code_hash: &'a Base58CryptoHash,
+    },
+    ContractUpgraded {
+        code_hash: &'a Base58CryptoHash,
+        source_metadata: &'a SourceMetadata,
+    },
+    StateMigrated {
+        from_version: &'a u32,
+        to_version: &'a u32,
+    },
+    PoolsMigrated {
+        from_pool_id: &'a u32,
+        to_pool_id: &'a u32,
+    },
     OwnerChanged {
         old_owner_id: &'a AccountId,
         new_owner_id: &'a AccountId,
     },
+    TcbPolicyUpdated {
+        tcb_policy: &'a TcbPolicy,
+    },
+    AppComposePolicyUpdated {
+        app_compose_policy: &'a AppComposePolicy,
+    },
+    PoolAppComposePolicyUpdated {
+        pool_id: &'a u32,
+        app_compose_policy: &'a Option<AppComposePolicy>,
+    },
+    WorkerPingTimeoutUpdated {
+        worker_ping_timeout_ms: &'a TimestampMs,
+    },
+    PoolWorkerPingTimeoutUpdated {
+        pool_id: &'a u32,
+        worker_ping_timeout_ms: &'a Option<TimestampMs>,
+    },
 }
 
 impl Event<'_> {
@@ -1,8 +1,9 @@
-use near_sdk::{ext_contract, AccountId, PublicKey};
+use near_sdk::{ext_contract, json_types::Base58CryptoHash, AccountId, PublicKey};
 
 #[allow(dead_code)]
 #[ext_contract(ext_intents_vault)]
 trait IntentsVaultContract {
     fn add_public_key(intents_contract_id: AccountId, public_key: PublicKey);
     fn remove_public_key(intents_contract_id: AccountId, public_key: PublicKey);
+    fn upgrade(code_hash: Base58CryptoHash);
 }
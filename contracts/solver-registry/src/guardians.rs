@@ -0,0 +1,233 @@
+use near_sdk::{borsh, near};
+
+use crate::*;
+
+#[near]
+impl Contract {
+    /// Set the guardians voting on compose hash changes and the number of votes required. Set
+    /// `None` to disable the multisig mode. Scheduled if the timelock delay is set.
+    ///
+    /// Once the multisig mode is enabled, the owner can change or disable the guardians only with
+    /// a timelock delay. Otherwise the guardians vote for the change with `vote_action`.
+    pub fn set_guardians(&mut self, guardian_set: Option<GuardianSet>) -> Option<ActionId> {
+        self.assert_owner();
+        if let Some(guardian_set) = guardian_set.as_ref() {
+            Self::assert_valid_guardian_set(guardian_set);
+        }
+        require!(
+            self.guardian_set.is_none() || self.timelock_delay_ms > 0,
+            "Guardian changes require guardian votes or a timelock delay"
+        );
+
+        self.schedule_or_execute(TimelockAction::SetGuardians { guardian_set })
+    }
+
+    /// Vote for an action requiring guardian votes in the multisig mode as a guardian: the
+    /// approval and removal of pre-launch scripts and docker images, the allowed environment
+    /// variables of compose hashes, and the change of the guardians. The action is
+    /// executed once the threshold of guardian votes is met. Returns whether it was executed.
+    pub fn vote_action(&mut self, action: TimelockAction) -> bool {
+        let guardian_set = self
+            .guardian_set
+            .clone()
+            .expect("Multisig mode is not enabled");
+        let guardian_id = env::predecessor_account_id();
+        require!(
+            guardian_set.guardians.contains(&guardian_id),
+            "Only guardians can vote"
+        );
+        Self::assert_valid_voted_action(&action);
+
+        let key = Self::action_votes_key(&action);
+        let mut votes = self
+            .action_votes
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| ActionVotes {
+                action: action.clone(),
+                voters: vec![],
+            });
+        require!(!votes.voters.contains(&guardian_id), "Already voted");
+        votes.voters.push(guardian_id.clone());
+        let vote_count = votes.voters.len() as u32;
+
+        Event::ActionVoted {
+            guardian_id: &guardian_id,
+            action: &action,
+            votes: &vote_count,
+            threshold: &guardian_set.threshold,
+        }
+        .emit();
+
+        if vote_count < guardian_set.threshold {
+            self.action_votes.insert(key, votes);
+            return false;
+        }
+
+        self.action_votes.remove(&key);
+        self.apply_voted_action(action);
+        true
+    }
+
+    /// Vote to approve or remove a compose hash as a guardian. The change takes effect once the
+    /// threshold of guardian votes is met. Returns whether the change took effect.
+    pub fn vote_compose_hash(&mut self, compose_hash: String, approve: bool) -> bool {
+        let guardian_set = self
+            .guardian_set
+            .clone()
+            .expect("Multisig mode is not enabled");
+        let guardian_id = env::predecessor_account_id();
+        require!(
+            guardian_set.guardians.contains(&guardian_id),
+            "Only guardians can vote"
+        );
+        DockerComposeHash::try_from_hex(compose_hash.clone()).expect("Invalid compose hash");
+        let is_approved = self.approved_compose_hashes.contains(&compose_hash);
+        if approve {
+            require!(!is_approved, "Compose hash already approved");
+        } else {
+            require!(is_approved, "Compose hash not found");
+        }
+
+        let mut votes = self
+            .compose_hash_votes
+            .get(&compose_hash)
+            .cloned()
+            .unwrap_or_default();
+        let voters = if approve {
+            &mut votes.approvals
+        } else {
+            &mut votes.removals
+        };
+        require!(!voters.contains(&guardian_id), "Already voted");
+        voters.push(guardian_id.clone());
+        let vote_count = voters.len() as u32;
+
+        Event::ComposeHashVoted {
+            guardian_id: &guardian_id,
+            compose_hash: &compose_hash,
+            approve: &approve,
+            votes: &vote_count,
+            threshold: &guardian_set.threshold,
+        }
+        .emit();
+
+        if vote_count < guardian_set.threshold {
+            self.compose_hash_votes.insert(compose_hash, votes);
+            return false;
+        }
+
+        // The votes on the other change are outdated once the compose hash changes
+        self.compose_hash_votes.remove(&compose_hash);
+        if approve {
+            self.apply_approve_compose_hash(compose_hash);
+        } else {
+            self.apply_remove_compose_hash(compose_hash);
+        }
+        true
+    }
+}
+
+impl Contract {
+    pub(crate) fn apply_set_guardians(&mut self, guardian_set: Option<GuardianSet>) {
+        // Votes of the previous guardians don't count for the new guardian set
+        self.compose_hash_votes.clear();
+        self.action_votes.clear();
+        self.guardian_set = guardian_set;
+
+        Event::GuardiansUpdated {
+            guardian_set: &self.guardian_set,
+        }
+        .emit();
+    }
+
+    pub(crate) fn assert_multisig_disabled(&self) {
+        require!(
+            self.guardian_set.is_none(),
+            "Compose hash changes require guardian votes"
+        );
+    }
+
+    pub(crate) fn action_votes_key(action: &TimelockAction) -> String {
+        hex::encode(env::sha256_array(
+            &borsh::to_vec(action).expect("Failed to serialize action"),
+        ))
+    }
+
+    fn assert_valid_guardian_set(guardian_set: &GuardianSet) {
+        let mut guardians = guardian_set.guardians.clone();
+        guardians.sort();
+        guardians.dedup();
+        require!(
+            guardians.len() == guardian_set.guardians.len(),
+            "Duplicate guardians"
+        );
+        require!(
+            guardian_set.threshold > 0
+                && guardian_set.threshold as usize <= guardian_set.guardians.len(),
+            "Invalid guardian threshold"
+        );
+    }
+
+    /// Validates the arguments of an action voted by guardians. The state is validated when the
+    /// action is executed.
+    fn assert_valid_voted_action(action: &TimelockAction) {
+        match action {
+            TimelockAction::ApprovePreLaunchScript {
+                pre_launch_script_hash,
+                ..
+            } => {
+                PreLaunchScriptHash::try_from_hex(pre_launch_script_hash.clone())
+                    .expect("Invalid pre-launch script hash");
+            }
+            TimelockAction::ApproveDockerImageHash {
+                docker_image_hash, ..
+            } => {
+                DockerImageHash::try_from_hex(docker_image_hash.clone())
+                    .expect("Invalid docker image hash");
+            }
+            TimelockAction::RemovePreLaunchScript { .. }
+            | TimelockAction::RemoveDockerImageHash { .. } => {}
+            TimelockAction::SetComposeHashAllowedEnvs {
+                allowed_envs: Some(allowed_envs),
+                ..
+            } => Self::assert_valid_env_names(allowed_envs),
+            TimelockAction::SetComposeHashAllowedEnvs {
+                allowed_envs: None, ..
+            } => {}
+            TimelockAction::SetGuardians { guardian_set } => {
+                if let Some(guardian_set) = guardian_set.as_ref() {
+                    Self::assert_valid_guardian_set(guardian_set);
+                }
+            }
+            _ => env::panic_str("Action not voted by guardians"),
+        }
+    }
+
+    fn apply_voted_action(&mut self, action: TimelockAction) {
+        match action {
+            TimelockAction::ApprovePreLaunchScript {
+                compose_hash,
+                pre_launch_script_hash,
+            } => self.apply_approve_pre_launch_script(compose_hash, pre_launch_script_hash),
+            TimelockAction::RemovePreLaunchScript {
+                compose_hash,
+                pre_launch_script_hash,
+            } => self.apply_remove_pre_launch_script(compose_hash, pre_launch_script_hash),
+            TimelockAction::ApproveDockerImageHash {
+                compose_hash,
+                docker_image_hash,
+            } => self.apply_approve_docker_image_hash(compose_hash, docker_image_hash),
+            TimelockAction::RemoveDockerImageHash {
+                compose_hash,
+                docker_image_hash,
+            } => self.apply_remove_docker_image_hash(compose_hash, docker_image_hash),
+            TimelockAction::SetComposeHashAllowedEnvs {
+                compose_hash,
+                allowed_envs,
+            } => self.apply_set_compose_hash_allowed_envs(compose_hash, allowed_envs),
+            TimelockAction::SetGuardians { guardian_set } => self.apply_set_guardians(guardian_set),
+            _ => env::panic_str("Action not voted by guardians"),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! Storage layouts of the previous state versions, read by the migrations in `upgrade.rs`.
+
+use near_sdk::{
+    near,
+    store::{IterableMap, IterableSet, LookupMap, Vector},
+    AccountId, PublicKey,
+};
+
+use crate::types::*;
+
+/// Contract state of version 0, the initial layout without a stored state version.
+#[near(serializers = [borsh])]
+pub struct ContractV0 {
+    pub owner_id: AccountId,
+    pub intents_contract_id: AccountId,
+    pub pools: Vector<PoolV0>,
+    pub approved_compose_hashes: IterableSet<String>,
+    pub worker_by_account_id: IterableMap<AccountId, WorkerV0>,
+    pub worker_ping_timeout_ms: TimestampMs,
+}
+
+#[near(serializers = [borsh])]
+pub struct PoolV0 {
+    pub token_ids: Vec<AccountId>,
+    pub amounts: Vec<Balance>,
+    pub fee: u32,
+    pub shares: LookupMap<AccountId, Balance>,
+    pub shares_total_supply: Balance,
+    pub worker_id: Option<AccountId>,
+    pub last_ping_timestamp_ms: TimestampMs,
+}
+
+#[near(serializers = [borsh])]
+pub struct WorkerV0 {
+    pub pool_id: u32,
+    pub checksum: String,
+    pub compose_hash: String,
+    pub public_key: PublicKey,
+}
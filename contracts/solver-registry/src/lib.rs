@@ -4,35 +4,53 @@ use dstack_sdk_types::dstack::TcbInfo;
 use hex::decode;
 use near_sdk::{
     assert_one_yocto,
-    env::{self, block_timestamp, block_timestamp_ms, sha256},
+    env::{self, block_timestamp_ms},
+    json_types::Base58CryptoHash,
     near, require,
-    store::{IterableMap, IterableSet, Vector},
+    store::{IterableMap, IterableSet, LookupMap, Vector},
     AccountId, Gas, NearToken, PanicOnDefault, Promise, PromiseError, PublicKey,
 };
 use std::str::FromStr;
 
 use crate::attestation::{
     app_compose::AppCompose,
-    attestation::{Attestation, DstackAttestation},
+    app_compose_policy::AppComposePolicy,
+    attestation::{Attestation, AttestationPolicy, DstackAttestation},
     collateral::Collateral,
-    hash::{DockerComposeHash, DockerImageHash},
+    hash::{DockerComposeHash, DockerImageHash, PreLaunchScriptHash},
     quote::QuoteBytes,
-    report_data::ReportData,
+    report_data::{ReportData, ReportDataV2, NONCE_SIZE},
+    tcb_policy::TcbPolicy,
 };
+use crate::clock::attestation_timestamp_ms;
 use crate::events::*;
 use crate::ext::*;
 use crate::pool::*;
 use crate::types::*;
+use crate::upgrade::PendingMigration;
 
 mod admin;
-mod attestation;
+pub mod attestation;
+mod clock;
+mod collateral_cache;
 mod events;
 mod ext;
+mod guardians;
+mod legacy;
+mod pause;
 pub mod pool;
+mod roles;
+mod timelock;
 mod token_receiver;
 pub mod types;
 mod upgrade;
+mod vault;
 mod view;
+mod worker_key;
+
+const REGISTRATION_CHALLENGE_STORAGE_DEPOSIT: NearToken = NearToken::from_millinear(10);
+const DEFAULT_REGISTRATION_CHALLENGE_TTL_MS: TimestampMs = 10 * 60 * 1000;
+const DEFAULT_MAX_COLLATERAL_AGE_MS: TimestampMs = 30 * 24 * 60 * 60 * 1000;
 
 const GAS_ADD_WORKER_KEY: Gas = Gas::from_tgas(20);
 const GAS_REMOVE_WORKER_KEY: Gas = Gas::from_tgas(20);
@@ -47,18 +65,67 @@ pub struct Worker {
     pub pool_id: u32,
     pub checksum: String,
     pub compose_hash: String,
+    /// Public key added to the pool's account in the intents contract for signing intents
     pub public_key: PublicKey,
+    /// TCB status of the worker's platform accepted during registration.
+    pub tcb_status: String,
+    /// Names of the environment variables allowed to be passed to the worker's CVM.
+    pub allowed_envs: Vec<String>,
+    /// Time until which the worker's attestation is valid, i.e. the next update of its collateral.
+    /// The worker can't ping after that until it refreshes its attestation.
+    pub attestation_valid_until_ms: TimestampMs,
 }
 
 #[near(contract_state)]
 #[derive(PanicOnDefault)]
 pub struct Contract {
     owner_id: AccountId,
+    /// Account proposed as the new owner, which must accept the ownership
+    pending_owner_id: Option<AccountId>,
     intents_contract_id: AccountId,
     pools: Vector<Pool>,
     approved_compose_hashes: IterableSet<String>,
+    compose_hash_configs: LookupMap<String, ComposeHashConfig>,
     worker_by_account_id: IterableMap<AccountId, Worker>,
     worker_ping_timeout_ms: TimestampMs,
+    tcb_policy: TcbPolicy,
+    app_compose_policy: AppComposePolicy,
+    /// Name of the RTMR3 event which measures the solver's docker image digest. The docker image
+    /// digest is verified only if set.
+    docker_image_hash_event: Option<String>,
+    registration_challenges: LookupMap<AccountId, RegistrationChallenge>,
+    registration_challenge_ttl_ms: TimestampMs,
+    /// Whether workers can register without a registration challenge, with the report data V1
+    allow_legacy_report_data: bool,
+    /// Curve types allowed for workers' intents public keys
+    allowed_key_types: Vec<KeyType>,
+    /// Maximum age of the collateral of workers' attestations
+    max_collateral_age_ms: TimestampMs,
+    /// Latest collateral by the lowercase hex-encoded FMSPC of the platform
+    cached_collaterals: LookupMap<String, Collateral>,
+    /// Accounts granted each role besides the owner
+    role_members: LookupMap<Role, IterableSet<AccountId>>,
+    /// Delay before scheduled sensitive admin actions can be executed. Actions are executed
+    /// immediately if zero.
+    timelock_delay_ms: TimestampMs,
+    scheduled_actions: IterableMap<ActionId, ScheduledAction>,
+    next_action_id: ActionId,
+    /// Guardians voting on compose hash changes. Compose hashes are approved and removed only by
+    /// guardian votes if set.
+    guardian_set: Option<GuardianSet>,
+    compose_hash_votes: IterableMap<String, ComposeHashVotes>,
+    /// Pending guardian votes on the other actions requiring them, by the hex-encoded sha256
+    /// hash of the action
+    action_votes: IterableMap<String, ActionVotes>,
+    paused_features: Vec<PausableFeature>,
+    /// Intents public keys left on the pools' accounts by failed rollbacks of key rotations, with
+    /// their pool IDs. They are removed by `revoke_all_worker_keys`.
+    orphaned_worker_keys: IterableMap<PublicKey, u32>,
+    /// Hash of the intents vault code published as a global contract, used by the accounts of
+    /// new pools and by vault upgrades
+    intents_vault_code_hash: Option<Base58CryptoHash>,
+    /// Pools and workers of the previous layout left to migrate after an upgrade
+    pending_migration: Option<PendingMigration>,
 }
 
 #[near]
@@ -70,31 +137,113 @@ impl Contract {
         intents_contract_id: AccountId,
         worker_ping_timeout_ms: TimestampMs,
     ) -> Self {
+        Self::write_state_version();
+
         Self {
             owner_id,
+            pending_owner_id: None,
             intents_contract_id,
             pools: Vector::new(Prefix::Pools),
             approved_compose_hashes: IterableSet::new(Prefix::ApprovedComposeHashes),
-            worker_by_account_id: IterableMap::new(Prefix::WorkerByAccountId),
+            compose_hash_configs: LookupMap::new(Prefix::ComposeHashConfigs),
+            worker_by_account_id: IterableMap::new(Prefix::Workers),
             worker_ping_timeout_ms,
+            tcb_policy: TcbPolicy::default(),
+            app_compose_policy: AppComposePolicy::default(),
+            docker_image_hash_event: None,
+            registration_challenges: LookupMap::new(Prefix::RegistrationChallenges),
+            registration_challenge_ttl_ms: DEFAULT_REGISTRATION_CHALLENGE_TTL_MS,
+            allow_legacy_report_data: false,
+            allowed_key_types: vec![KeyType::Ed25519, KeyType::Secp256k1],
+            max_collateral_age_ms: DEFAULT_MAX_COLLATERAL_AGE_MS,
+            cached_collaterals: LookupMap::new(Prefix::CachedCollaterals),
+            role_members: LookupMap::new(Prefix::Roles),
+            timelock_delay_ms: 0,
+            scheduled_actions: IterableMap::new(Prefix::ScheduledActions),
+            next_action_id: 0,
+            guardian_set: None,
+            compose_hash_votes: IterableMap::new(Prefix::ComposeHashVotes),
+            action_votes: IterableMap::new(Prefix::ActionVotes),
+            paused_features: vec![],
+            orphaned_worker_keys: IterableMap::new(Prefix::OrphanedWorkerKeys),
+            intents_vault_code_hash: None,
+            pending_migration: None,
+        }
+    }
+
+    /// Request a registration challenge for the pool. The worker must include the returned nonce
+    /// in the report data (V2) of its attestation and register before the challenge expires.
+    /// Requesting a new challenge replaces the pending one of the worker.
+    ///
+    /// The attached deposit covers the storage of the challenge, and is refunded once the
+    /// challenge is consumed or replaced. The excess deposit is refunded right away.
+    #[payable]
+    pub fn request_registration_challenge(&mut self, pool_id: u32) -> RegistrationChallenge {
+        require!(
+            env::attached_deposit() >= REGISTRATION_CHALLENGE_STORAGE_DEPOSIT,
+            "Not enough attached deposit"
+        );
+        require!(self.pool(pool_id).is_some(), "Pool not found");
+
+        let worker_id = env::predecessor_account_id();
+        let challenge = RegistrationChallenge {
+            pool_id,
+            nonce: hex::encode(env::random_seed_array()),
+            expires_at_ms: block_timestamp_ms() + self.registration_challenge_ttl_ms,
+        };
+        let replaced_challenge = self
+            .registration_challenges
+            .insert(worker_id.clone(), challenge.clone());
+
+        let mut refund =
+            env::attached_deposit().saturating_sub(REGISTRATION_CHALLENGE_STORAGE_DEPOSIT);
+        if replaced_challenge.is_some() {
+            refund = refund.saturating_add(REGISTRATION_CHALLENGE_STORAGE_DEPOSIT);
+        }
+        if !refund.is_zero() {
+            Promise::new(worker_id.clone()).transfer(refund);
+        }
+
+        Event::RegistrationChallengeIssued {
+            worker_id: &worker_id,
+            pool_id: &pool_id,
+            nonce: &challenge.nonce,
+            expires_at_ms: &challenge.expires_at_ms,
         }
+        .emit();
+
+        challenge
     }
 
     /// Register worker with TEE attestation. The worker needs to running inside a CVM with one of the approved docker compose hashes.
     ///
     /// The current TEE attestation module reuses the implementation from [NEAR MPC](https://github.com/near/mpc) TEE attestation with slight change.
     /// Find more details about TEE attestation module in `attestation/README.md`.
+    ///
+    /// The quote's report data must be of V2 with the nonce of the worker's pending registration
+    /// challenge, which binds the quote to the worker, the pool, this registry and the nonce. The
+    /// challenge is consumed by the registration. Report data V1 without a challenge is accepted
+    /// only if legacy report data is allowed.
+    ///
+    /// The report data V2 commits to both the signer's access key and `intents_public_key`, which is
+    /// added to the intents contract for signing intents. With the report data V1, the intents
+    /// public key must be the signer's public key.
+    ///
+    /// The quote is verified against the cached collateral for the FMSPC of the quote if exists,
+    /// otherwise `collateral` is required.
     #[payable]
     pub fn register_worker(
         &mut self,
         pool_id: u32,
         quote_hex: String,
-        collateral: String,
+        collateral: Option<String>,
         checksum: String,
         tcb_info: String,
+        intents_public_key: PublicKey,
     ) -> Promise {
         assert_one_yocto();
-        let pool = self.pools.get(pool_id).expect("Pool not found");
+        self.assert_not_paused(PausableFeature::RegisterWorker);
+        let pool = self.pool(pool_id).expect("Pool not found");
 
         // Register new worker is allowed only if there's no active worker and the worker is not already registered
         let worker_id = env::predecessor_account_id();
@@ -106,58 +255,22 @@ impl Contract {
             pool.worker_id.is_none() || pool.worker_id.as_ref().unwrap() != &worker_id,
             "Worker already registered"
         );
+        let inactive_worker_id = pool.worker_id.clone();
 
-        // Parse the attestation components
-        let quote_bytes = QuoteBytes::from(decode(&quote_hex).expect("Invalid quote hex"));
-        let collateral_data = Collateral::from_str(&collateral).expect("Invalid collateral format");
-        let tcb_info_data: TcbInfo =
-            serde_json::from_str(&tcb_info).expect("Invalid TCB info format");
-
-        // Create the attestation
-        let attestation = Attestation::Dstack(DstackAttestation::new(
-            quote_bytes,
-            collateral_data,
-            tcb_info_data.clone(),
-        ));
-
-        // Get the signer's public key
-        let public_key = env::signer_account_pk();
-        // Create expected report data from the public key
-        let expected_report_data = ReportData::new(public_key.clone());
-
-        // Get current timestamp in seconds
-        let timestamp_s = block_timestamp() / 1_000_000_000;
-
-        // For now, allow all docker image hashes as we only verify the docker compose hash
-        let allowed_docker_image_hashes: Vec<DockerImageHash> = vec![];
-        let allowed_docker_compose_hashes: Vec<DockerComposeHash> = self
-            .approved_compose_hashes
-            .iter()
-            .map(|hash| DockerComposeHash::try_from_hex(hash).expect("Invalid compose hash"))
-            .collect();
-
-        // Verify the attestation
-        require!(
-            attestation.verify(
-                expected_report_data,
-                timestamp_s,
-                &allowed_docker_image_hashes,
-                &allowed_docker_compose_hashes,
-            ),
-            "Attestation verification failed"
+        let worker = self.verify_worker_attestation(
+            pool_id,
+            &quote_hex,
+            collateral.as_deref(),
+            checksum,
+            &tcb_info,
+            intents_public_key,
         );
 
-        // Extract docker compose hash from TCB info
-        let docker_compose_hash = self
-            .find_approved_compose_hash(&tcb_info_data, &allowed_docker_compose_hashes)
-            .expect("Invalid docker compose hash");
-        let docker_compose_hash_hex = docker_compose_hash.as_hex();
-
         // Remove the public key of the inactive worker if exists
-        if let Some(inactive_worker_id) = pool.worker_id.as_ref() {
+        if let Some(inactive_worker_id) = inactive_worker_id {
             let inactive_worker = self
                 .worker_by_account_id
-                .get(inactive_worker_id)
+                .get(&inactive_worker_id)
                 .expect("Worker not registered");
             ext_intents_vault::ext(self.get_pool_account_id(pool_id))
                 .with_attached_deposit(NearToken::from_yoctonear(1))
@@ -171,22 +284,10 @@ impl Contract {
                     Self::ext(env::current_account_id())
                         .with_static_gas(GAS_REMOVE_WORKER_KEY_CALLBACK)
                         .with_unused_gas_weight(0)
-                        .on_inactive_worker_key_removed(
-                            worker_id,
-                            pool_id,
-                            public_key,
-                            docker_compose_hash_hex,
-                            checksum,
-                        ),
+                        .on_inactive_worker_key_removed(worker_id, worker),
                 )
         } else {
-            self.register_new_public_key(
-                worker_id,
-                pool_id,
-                public_key,
-                docker_compose_hash_hex,
-                checksum,
-            )
+            self.register_new_public_key(worker_id, worker)
         }
     }
 
@@ -194,23 +295,20 @@ impl Contract {
     pub fn on_inactive_worker_key_removed(
         &mut self,
         worker_id: AccountId,
-        pool_id: u32,
-        public_key: PublicKey,
-        docker_compose_hash_hex: String,
-        checksum: String,
+        worker: Worker,
         #[callback_result] call_result: Result<(), PromiseError>,
     ) -> Promise {
         if call_result.is_ok() {
             // remove inactive worker
-            let pool = self.pools.get(pool_id).expect("Pool not found");
-            let inactive_worker_id = pool.worker_id.as_ref().expect("Pool has no worker");
+            let pool = self.pool(worker.pool_id).expect("Pool not found");
+            let inactive_worker_id = pool.worker_id.clone().expect("Pool has no worker");
             let inactive_worker = self
                 .worker_by_account_id
-                .remove(inactive_worker_id)
+                .remove(&inactive_worker_id)
                 .expect("Worker not registered");
             Event::WorkerRemoved {
-                worker_id: inactive_worker_id,
-                pool_id: &pool_id,
+                worker_id: &inactive_worker_id,
+                pool_id: &worker.pool_id,
                 public_key: &inactive_worker.public_key,
                 compose_hash: &inactive_worker.compose_hash,
                 checksum: &inactive_worker.checksum,
@@ -218,13 +316,7 @@ impl Contract {
             .emit();
 
             // register new worker and its key
-            self.register_new_public_key(
-                worker_id,
-                pool_id,
-                public_key,
-                docker_compose_hash_hex,
-                checksum,
-            )
+            self.register_new_public_key(worker_id, worker)
         } else {
             env::panic_str("Failed to remove inactive worker key");
         }
@@ -234,54 +326,49 @@ impl Contract {
     pub fn on_worker_key_added(
         &mut self,
         worker_id: AccountId,
-        pool_id: u32,
-        public_key: PublicKey,
-        compose_hash: String,
-        checksum: String,
+        worker: Worker,
         #[callback_result] call_result: Result<(), PromiseError>,
     ) {
         if call_result.is_ok() {
-            self.worker_by_account_id.insert(
-                worker_id.clone(),
-                Worker {
-                    pool_id,
-                    checksum: checksum.clone(),
-                    compose_hash: compose_hash.clone(),
-                    public_key: public_key.clone(),
-                },
-            );
-
             // Update the pool with the worker ID and last ping timestamp
-            let pool = self.pools.get_mut(pool_id).expect("Pool not found");
+            let pool = self.pool_mut(worker.pool_id).expect("Pool not found");
             pool.worker_id = Some(worker_id.clone());
             pool.last_ping_timestamp_ms = block_timestamp_ms();
             self.pools.flush();
 
             Event::WorkerRegistered {
                 worker_id: &worker_id,
-                pool_id: &pool_id,
-                public_key: &public_key,
-                compose_hash: &compose_hash,
-                checksum: &checksum,
+                pool_id: &worker.pool_id,
+                public_key: &worker.public_key,
+                compose_hash: &worker.compose_hash,
+                checksum: &worker.checksum,
+                tcb_status: &worker.tcb_status,
             }
             .emit();
+
+            self.worker_by_account_id.insert(worker_id, worker);
         }
     }
 
     /// Heartbeat to notify the pool that the worker is still alive.
     pub fn ping(&mut self) {
+        self.assert_not_paused(PausableFeature::Ping);
         let worker_id = env::predecessor_account_id();
         let worker = self
             .get_worker(worker_id.clone())
             .expect("Worker not found");
         self.assert_approved_compose_hash(&worker.compose_hash);
 
-        let pool = self.pools.get_mut(worker.pool_id).expect("Pool not found");
+        let pool = self.pool_mut(worker.pool_id).expect("Pool not found");
         let registered_worker_id = pool.worker_id.as_ref().expect("Worker not registered");
         require!(
             registered_worker_id == &worker_id,
             "Only the registered worker can ping"
         );
+        require!(
+            attestation_timestamp_ms() < worker.attestation_valid_until_ms,
+            "Worker attestation expired"
+        );
 
         pool.last_ping_timestamp_ms = block_timestamp_ms();
         self.pools.flush();
@@ -293,9 +380,210 @@ impl Contract {
         }
         .emit();
     }
+
+    /// Refresh the TEE attestation of the pool's registered worker with a fresh quote and
+    /// collateral, e.g. after its attestation expired. The report data follows the same rules as
+    /// `register_worker` and must commit to the worker's current intents public key.
+    #[payable]
+    pub fn refresh_worker_attestation(
+        &mut self,
+        quote_hex: String,
+        collateral: Option<String>,
+        tcb_info: String,
+    ) {
+        assert_one_yocto();
+        let worker_id = env::predecessor_account_id();
+        let worker = self
+            .get_worker(worker_id.clone())
+            .expect("Worker not found");
+
+        let pool = self.pool(worker.pool_id).expect("Pool not found");
+        require!(
+            pool.worker_id.as_ref() == Some(&worker_id),
+            "Only the registered worker can refresh its attestation"
+        );
+
+        let refreshed_worker = self.verify_worker_attestation(
+            worker.pool_id,
+            &quote_hex,
+            collateral.as_deref(),
+            worker.checksum,
+            &tcb_info,
+            worker.public_key,
+        );
+
+        Event::WorkerAttestationRefreshed {
+            worker_id: &worker_id,
+            pool_id: &refreshed_worker.pool_id,
+            compose_hash: &refreshed_worker.compose_hash,
+            checksum: &refreshed_worker.checksum,
+            tcb_status: &refreshed_worker.tcb_status,
+            attestation_valid_until_ms: &refreshed_worker.attestation_valid_until_ms,
+        }
+        .emit();
+
+        self.worker_by_account_id
+            .insert(worker_id, refreshed_worker);
+    }
 }
 
 impl Contract {
+    /// Verifies the TEE attestation of the calling worker for the pool, which must commit to the
+    /// intents public key. Consumes the worker's pending registration challenge if exists.
+    fn verify_worker_attestation(
+        &mut self,
+        pool_id: u32,
+        quote_hex: &str,
+        collateral: Option<&str>,
+        checksum: String,
+        tcb_info: &str,
+        intents_public_key: PublicKey,
+    ) -> Worker {
+        let pool_app_compose_policy = self
+            .pool(pool_id)
+            .expect("Pool not found")
+            .app_compose_policy
+            .clone();
+        require!(
+            self.allowed_key_types
+                .contains(&KeyType::from(&intents_public_key)),
+            "Key type not allowed"
+        );
+
+        // Get current timestamp in seconds
+        let timestamp_s = attestation_timestamp_ms() / 1000;
+
+        // Parse the attestation components
+        let quote_bytes = QuoteBytes::from(decode(quote_hex).expect("Invalid quote hex"));
+        let collateral_data = self.resolve_collateral(&quote_bytes, collateral, timestamp_s);
+        let tcb_info_data: TcbInfo =
+            serde_json::from_str(tcb_info).expect("Invalid TCB info format");
+        let app_compose: AppCompose =
+            serde_json::from_str(&tcb_info_data.app_compose).expect("Invalid app compose format");
+
+        // Create the attestation
+        let attestation = Attestation::Dstack(DstackAttestation::new(
+            quote_bytes,
+            collateral_data,
+            tcb_info_data.clone(),
+        ));
+
+        // Create expected report data from the public keys and the registration challenge
+        let expected_report_data = self.take_expected_report_data(pool_id, &intents_public_key);
+
+        // Extract docker compose hash from TCB info
+        let docker_compose_hash = self
+            .find_approved_compose_hash(&app_compose)
+            .expect("Invalid docker compose hash");
+        let compose_hash_config =
+            self.get_compose_hash_config_or_default(&docker_compose_hash.as_hex());
+
+        // Pre-launch scripts approved to run with the docker compose file
+        let allowed_pre_launch_script_hashes: Vec<PreLaunchScriptHash> = compose_hash_config
+            .pre_launch_script_hashes
+            .iter()
+            .map(|hash| {
+                PreLaunchScriptHash::try_from_hex(hash).expect("Invalid pre-launch script hash")
+            })
+            .collect();
+
+        // Docker images approved to run with the docker compose file
+        let allowed_docker_image_hashes: Vec<DockerImageHash> = compose_hash_config
+            .docker_image_hashes
+            .iter()
+            .map(|hash| DockerImageHash::try_from_hex(hash).expect("Invalid docker image hash"))
+            .collect();
+
+        // Use the pool's app compose policy if exists, with the allowed environment variables of
+        // the docker compose hash if set
+        let mut app_compose_policy =
+            pool_app_compose_policy.unwrap_or_else(|| self.app_compose_policy.clone());
+        if compose_hash_config.allowed_envs.is_some() {
+            app_compose_policy.allowed_envs = compose_hash_config.allowed_envs;
+        }
+
+        // Verify the attestation
+        let verified_attestation = attestation
+            .verify(
+                expected_report_data,
+                timestamp_s,
+                &AttestationPolicy {
+                    tcb_policy: &self.tcb_policy,
+                    app_compose_policy: &app_compose_policy,
+                    docker_image_hash_event: self.docker_image_hash_event.as_deref(),
+                    allowed_docker_image_hashes: &allowed_docker_image_hashes,
+                    allowed_docker_compose_hashes: &[docker_compose_hash.clone()],
+                    allowed_pre_launch_script_hashes: &allowed_pre_launch_script_hashes,
+                    max_collateral_age_s: self.max_collateral_age_ms / 1000,
+                },
+            )
+            .unwrap_or_else(|| env::panic_str("Attestation verification failed"));
+
+        Worker {
+            pool_id,
+            checksum,
+            compose_hash: docker_compose_hash.as_hex(),
+            public_key: intents_public_key,
+            tcb_status: verified_attestation.tcb_status,
+            allowed_envs: app_compose.allowed_envs,
+            attestation_valid_until_ms: verified_attestation.valid_until_s.saturating_mul(1000),
+        }
+    }
+
+    /// Builds the report data expected in the attestation of the calling worker. Consumes the
+    /// worker's pending registration challenge and refunds its storage deposit, or falls back to
+    /// the legacy report data V1 if allowed.
+    fn take_expected_report_data(
+        &mut self,
+        pool_id: u32,
+        intents_public_key: &PublicKey,
+    ) -> ReportData {
+        let worker_id = env::predecessor_account_id();
+        let signer_public_key = env::signer_account_pk();
+        match self.registration_challenges.remove(&worker_id) {
+            Some(challenge) => {
+                require!(
+                    challenge.pool_id == pool_id,
+                    "Registration challenge issued for another pool"
+                );
+                require!(
+                    block_timestamp_ms() < challenge.expires_at_ms,
+                    "Registration challenge expired"
+                );
+                let nonce: [u8; NONCE_SIZE] = decode(&challenge.nonce)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .expect("Invalid report data nonce");
+                // The storage of the challenge is released
+                Promise::new(worker_id.clone()).transfer(REGISTRATION_CHALLENGE_STORAGE_DEPOSIT);
+                ReportData::V2(ReportDataV2::new(
+                    signer_public_key,
+                    intents_public_key.clone(),
+                    worker_id,
+                    pool_id,
+                    env::current_account_id(),
+                    nonce,
+                ))
+            }
+            None => {
+                require!(
+                    self.allow_legacy_report_data,
+                    "Registration challenge not found"
+                );
+                require!(
+                    *intents_public_key == signer_public_key,
+                    "Intents public key must be the signer's public key with report data V1"
+                );
+                // Report data V1 doesn't commit to the curve type of the public key
+                require!(
+                    KeyType::from(&signer_public_key) == KeyType::Ed25519,
+                    "Report data V1 only supports ed25519 keys"
+                );
+                ReportData::new(signer_public_key)
+            }
+        }
+    }
+
     fn assert_approved_compose_hash(&self, compose_hash: &String) {
         require!(
             self.approved_compose_hashes.contains(compose_hash),
@@ -303,50 +591,305 @@ impl Contract {
         );
     }
 
-    fn find_approved_compose_hash(
+    fn find_approved_compose_hash(&self, app_compose: &AppCompose) -> Option<DockerComposeHash> {
+        let compose_hash = app_compose.docker_compose_hash();
+        self.approved_compose_hashes
+            .contains(&compose_hash.as_hex())
+            .then_some(compose_hash)
+    }
+
+    pub(crate) fn get_compose_hash_config_or_default(
         &self,
-        tcb_info: &TcbInfo,
-        allowed_hashes: &[DockerComposeHash],
-    ) -> Option<DockerComposeHash> {
-        let app_compose: AppCompose = match serde_json::from_str(&tcb_info.app_compose) {
-            Ok(compose) => compose,
-            Err(e) => {
-                tracing::error!("Failed to parse app_compose JSON: {:?}", e);
-                return None;
-            }
-        };
-        let compose_hash = sha256(app_compose.docker_compose_file.as_bytes());
-        allowed_hashes
-            .iter()
-            .find(|hash| hash.as_hex() == hex::encode(&compose_hash))
+        compose_hash: &String,
+    ) -> ComposeHashConfig {
+        self.compose_hash_configs
+            .get(compose_hash)
             .cloned()
+            .unwrap_or_default()
     }
 
-    fn register_new_public_key(
-        &mut self,
-        worker_id: AccountId,
-        pool_id: u32,
-        public_key: PublicKey,
-        docker_compose_hash_hex: String,
-        checksum: String,
-    ) -> Promise {
+    fn register_new_public_key(&mut self, worker_id: AccountId, worker: Worker) -> Promise {
         // Add the public key to the intents vault
-        ext_intents_vault::ext(self.get_pool_account_id(pool_id))
+        ext_intents_vault::ext(self.get_pool_account_id(worker.pool_id))
             .with_attached_deposit(NearToken::from_yoctonear(1))
             .with_static_gas(GAS_ADD_WORKER_KEY)
             .with_unused_gas_weight(0)
-            .add_public_key(self.intents_contract_id.clone(), public_key.clone())
+            .add_public_key(self.intents_contract_id.clone(), worker.public_key.clone())
             .then(
                 Self::ext(env::current_account_id())
                     .with_static_gas(GAS_ADD_WORKER_KEY_CALLBACK)
                     .with_unused_gas_weight(0)
-                    .on_worker_key_added(
-                        worker_id,
-                        pool_id,
-                        public_key,
-                        docker_compose_hash_hex,
-                        checksum,
-                    ),
+                    .on_worker_key_added(worker_id, worker),
             )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::MockAction;
+    use near_sdk::test_utils::{get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const RANDOM_SEED: [u8; 32] = [7u8; 32];
+
+    fn worker_id() -> AccountId {
+        "alice.near".parse().unwrap()
+    }
+
+    fn worker_public_key() -> PublicKey {
+        "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+            .parse()
+            .unwrap()
+    }
+
+    fn set_context(attached_deposit: NearToken) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("solver-registry.near".parse().unwrap())
+            .predecessor_account_id(worker_id())
+            .signer_account_id(worker_id())
+            .signer_account_pk(worker_public_key())
+            .attached_deposit(attached_deposit)
+            .random_seed(RANDOM_SEED)
+            .build());
+    }
+
+    fn create_contract() -> Contract {
+        let mut contract = Contract::new(
+            "owner.near".parse().unwrap(),
+            "intents.near".parse().unwrap(),
+            60 * 1000,
+        );
+        contract.pools.push(Pool::new(
+            vec!["wrap.near".parse().unwrap(), "usdc.near".parse().unwrap()],
+            300,
+            Base58CryptoHash::from([0u8; 32]),
+        ));
+        contract
+    }
+
+    fn refunded_deposits() -> Vec<NearToken> {
+        get_created_receipts()
+            .into_iter()
+            .filter(|receipt| receipt.receiver_id == worker_id())
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                MockAction::Transfer { deposit, .. } => Some(deposit),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_take_expected_report_data_consumes_registration_challenge() {
+        set_context(REGISTRATION_CHALLENGE_STORAGE_DEPOSIT);
+        let mut contract = create_contract();
+        let challenge = contract.request_registration_challenge(0);
+        assert_eq!(challenge.nonce, hex::encode(RANDOM_SEED));
+        assert!(refunded_deposits().is_empty());
+
+        set_context(NearToken::from_yoctonear(0));
+        let intents_public_key = worker_public_key();
+        let report_data = contract.take_expected_report_data(0, &intents_public_key);
+
+        let expected_report_data = ReportDataV2::new(
+            worker_public_key(),
+            intents_public_key,
+            worker_id(),
+            0,
+            "solver-registry.near".parse().unwrap(),
+            RANDOM_SEED,
+        );
+        assert!(matches!(report_data, ReportData::V2(_)));
+        assert_eq!(report_data.to_bytes(), expected_report_data.to_bytes());
+        assert!(contract.registration_challenges.get(&worker_id()).is_none());
+        assert_eq!(
+            refunded_deposits(),
+            vec![REGISTRATION_CHALLENGE_STORAGE_DEPOSIT]
+        );
+    }
+
+    #[test]
+    fn test_request_registration_challenge_refunds_replaced_challenge() {
+        set_context(REGISTRATION_CHALLENGE_STORAGE_DEPOSIT);
+        let mut contract = create_contract();
+        contract.request_registration_challenge(0);
+
+        set_context(
+            REGISTRATION_CHALLENGE_STORAGE_DEPOSIT.saturating_add(NearToken::from_yoctonear(1)),
+        );
+        contract.request_registration_challenge(0);
+        assert_eq!(
+            refunded_deposits(),
+            vec![
+                REGISTRATION_CHALLENGE_STORAGE_DEPOSIT.saturating_add(NearToken::from_yoctonear(1))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_register_secp256k1_intents_public_key() {
+        let intents_public_key: PublicKey = "secp256k1:qMoRgcoXai4mBPsdbHi1wfyxF9TdbPCF4qSDQTRP3TfescSRoUdSx6nmeQoN3aiwGzwMyGXAb1gUjBTv5AY8DXj"
+            .parse()
+            .unwrap();
+
+        set_context(REGISTRATION_CHALLENGE_STORAGE_DEPOSIT);
+        let mut contract = create_contract();
+        contract.request_registration_challenge(0);
+
+        // The report data V2 commits to the secp256k1 intents public key besides the signer's key
+        set_context(NearToken::from_yoctonear(0));
+        let report_data = contract.take_expected_report_data(0, &intents_public_key);
+        let expected_report_data = ReportDataV2::new(
+            worker_public_key(),
+            intents_public_key.clone(),
+            worker_id(),
+            0,
+            "solver-registry.near".parse().unwrap(),
+            RANDOM_SEED,
+        );
+        assert_eq!(report_data.to_bytes(), expected_report_data.to_bytes());
+
+        // The intents public key is added to the pool's vault
+        set_context(NearToken::from_yoctonear(0));
+        let worker = Worker {
+            pool_id: 0,
+            checksum: "checksum".to_string(),
+            compose_hash: "compose_hash".to_string(),
+            public_key: intents_public_key.clone(),
+            tcb_status: "UpToDate".to_string(),
+            allowed_envs: vec![],
+            attestation_valid_until_ms: 0,
+        };
+        let _ = contract.register_new_public_key(worker_id(), worker.clone());
+        let added_public_keys: Vec<PublicKey> = get_created_receipts()
+            .into_iter()
+            .filter(|receipt| receipt.receiver_id.as_str() == "pool-0.solver-registry.near")
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                MockAction::FunctionCallWeight {
+                    method_name, args, ..
+                } if method_name == b"add_public_key" => {
+                    let args: serde_json::Value = serde_json::from_slice(&args).unwrap();
+                    serde_json::from_value(args["public_key"].clone()).ok()
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(added_public_keys, vec![intents_public_key.clone()]);
+
+        contract.on_worker_key_added(worker_id(), worker, Ok(()));
+        assert_eq!(
+            contract.get_worker(worker_id()).unwrap().public_key,
+            intents_public_key
+        );
+        assert_eq!(contract.pools.get(0).unwrap().worker_id, Some(worker_id()));
+    }
+
+    #[test]
+    fn test_rotated_worker_key_removed_when_worker_revoked() {
+        set_context(NearToken::from_yoctonear(0));
+        let mut contract = create_contract();
+        let new_public_key: PublicKey = "ed25519:DcA2MzgpJbrUATQLLceocVckhhAqrkingax4oJ9kZ847"
+            .parse()
+            .unwrap();
+        let new_worker = Worker {
+            pool_id: 0,
+            checksum: "checksum".to_string(),
+            compose_hash: "compose_hash".to_string(),
+            public_key: new_public_key.clone(),
+            tcb_status: "UpToDate".to_string(),
+            allowed_envs: vec![],
+            attestation_valid_until_ms: 0,
+        };
+
+        // The worker was revoked before the old key was removed, so the new key is removed too
+        let _ = contract.on_rotated_old_worker_key_removed(
+            worker_id(),
+            worker_public_key(),
+            new_worker,
+            Ok(()),
+        );
+        assert!(contract.get_worker(worker_id()).is_none());
+        let removed_public_keys: Vec<PublicKey> = get_created_receipts()
+            .into_iter()
+            .filter(|receipt| receipt.receiver_id.as_str() == "pool-0.solver-registry.near")
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                MockAction::FunctionCallWeight {
+                    method_name, args, ..
+                } if method_name == b"remove_public_key" => {
+                    let args: serde_json::Value = serde_json::from_slice(&args).unwrap();
+                    serde_json::from_value(args["public_key"].clone()).ok()
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(removed_public_keys, vec![new_public_key]);
+    }
+
+    #[test]
+    fn test_migrate_v0_workers() {
+        let block_timestamp_ms: TimestampMs = 1_756_857_600_000;
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("solver-registry.near".parse().unwrap())
+            .predecessor_account_id("solver-registry.near".parse().unwrap())
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .block_timestamp(block_timestamp_ms * 1_000_000)
+            .build());
+
+        let mut pools = Vector::new(Prefix::Pools);
+        pools.push(legacy::PoolV0 {
+            token_ids: vec!["wrap.near".parse().unwrap(), "usdc.near".parse().unwrap()],
+            amounts: vec![0, 0],
+            fee: 300,
+            shares: LookupMap::new(Prefix::PoolShares),
+            shares_total_supply: 0,
+            worker_id: Some(worker_id()),
+            last_ping_timestamp_ms: 0,
+        });
+        pools.flush();
+        let mut workers = IterableMap::new(Prefix::WorkerByAccountId);
+        workers.insert(
+            worker_id(),
+            legacy::WorkerV0 {
+                pool_id: 0,
+                checksum: "checksum".to_string(),
+                compose_hash: "compose_hash".to_string(),
+                public_key: worker_public_key(),
+            },
+        );
+        workers.flush();
+        env::state_write(&legacy::ContractV0 {
+            owner_id: "owner.near".parse().unwrap(),
+            intents_contract_id: "intents.near".parse().unwrap(),
+            pools,
+            approved_compose_hashes: IterableSet::new(Prefix::ApprovedComposeHashes),
+            worker_by_account_id: workers,
+            worker_ping_timeout_ms: 60 * 1000,
+        });
+
+        let contract = Contract::migrate();
+        assert!(contract.pending_migration.is_none());
+        assert_eq!(contract.pools.get(0).unwrap().worker_id, Some(worker_id()));
+        let worker = contract.get_worker(worker_id()).unwrap();
+        assert_eq!(worker.pool_id, 0);
+        assert_eq!(worker.checksum, "checksum");
+        assert_eq!(worker.compose_hash, "compose_hash");
+        assert_eq!(worker.public_key, worker_public_key());
+        assert_eq!(worker.tcb_status, "UpToDate");
+        // The migrated worker must refresh its attestation within the maximum collateral age
+        assert_eq!(
+            worker.attestation_valid_until_ms,
+            block_timestamp_ms + DEFAULT_MAX_COLLATERAL_AGE_MS
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Registration challenge not found")]
+    fn test_take_expected_report_data_without_registration_challenge() {
+        set_context(NearToken::from_yoctonear(0));
+        let mut contract = create_contract();
+        contract.take_expected_report_data(0, &worker_public_key());
+    }
+}
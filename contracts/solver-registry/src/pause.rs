@@ -0,0 +1,186 @@
+use near_sdk::{near, Gas, PromiseError};
+
+use crate::*;
+
+const GAS_REVOKE_WORKER_KEY_CALLBACK: Gas = Gas::from_tgas(10);
+const DEFAULT_REVOKE_WORKER_KEYS_LIMIT: u32 = 10;
+
+#[near]
+impl Contract {
+    /// Pause the features (Pauser)
+    pub fn pause(&mut self, features: Vec<PausableFeature>) {
+        self.assert_role(Role::Pauser);
+        require!(!features.is_empty(), "No feature to pause");
+
+        for feature in features.iter() {
+            if !self.paused_features.contains(feature) {
+                self.paused_features.push(*feature);
+            }
+        }
+
+        Event::Paused {
+            features: &features,
+        }
+        .emit();
+    }
+
+    /// Unpause the features (Pauser)
+    pub fn unpause(&mut self, features: Vec<PausableFeature>) {
+        self.assert_role(Role::Pauser);
+        require!(!features.is_empty(), "No feature to unpause");
+
+        self.paused_features
+            .retain(|feature| !features.contains(feature));
+
+        Event::Unpaused {
+            features: &features,
+        }
+        .emit();
+    }
+
+    /// Emergency action to cut all workers off from the pools' funds (Pauser). Pauses worker
+    /// registration, pings and deposits, and removes the keys of the registered workers of up to
+    /// `limit` pools from `from_index` from the pools' accounts in the intents contract. The workers
+    /// are removed once their keys are removed. The keys which fail to be removed are reported with
+    /// `worker_key_revocation_failed` events.
+    pub fn revoke_all_worker_keys(&mut self, from_index: Option<u32>, limit: Option<u32>) {
+        self.assert_role(Role::Pauser);
+
+        let newly_paused: Vec<PausableFeature> = [
+            PausableFeature::RegisterWorker,
+            PausableFeature::Ping,
+            PausableFeature::Deposit,
+        ]
+        .into_iter()
+        .filter(|feature| !self.paused_features.contains(feature))
+        .collect();
+        if !newly_paused.is_empty() {
+            self.paused_features.extend(newly_paused.iter());
+            Event::Paused {
+                features: &newly_paused,
+            }
+            .emit();
+        }
+
+        let from_index = from_index.unwrap_or(0);
+        let limit = limit.unwrap_or(DEFAULT_REVOKE_WORKER_KEYS_LIMIT);
+        let end_index = from_index.saturating_add(limit).min(self.pools.len());
+        for pool_id in from_index..end_index {
+            let Some(worker_id) = self.pool(pool_id).and_then(|pool| pool.worker_id.clone()) else {
+                continue;
+            };
+            let Some(worker) = self.worker_by_account_id.get(&worker_id) else {
+                continue;
+            };
+
+            ext_intents_vault::ext(self.get_pool_account_id(pool_id))
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_REMOVE_WORKER_KEY)
+                .with_unused_gas_weight(0)
+                .remove_public_key(self.intents_contract_id.clone(), worker.public_key.clone())
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(GAS_REVOKE_WORKER_KEY_CALLBACK)
+                        .with_unused_gas_weight(0)
+                        .on_worker_key_revoked(pool_id, worker_id, worker.public_key.clone()),
+                );
+        }
+    }
+
+    /// Remove up to `limit` worker keys orphaned by failed key rotation rollbacks from
+    /// `from_index` of `get_orphaned_worker_keys` from the pools' accounts in the intents contract
+    /// (Pauser). The keys are no longer orphaned once removed, so the next batch starts from the
+    /// index 0 after the callbacks of the previous one. The keys which fail to be removed are
+    /// reported with `worker_key_revocation_failed` events.
+    pub fn revoke_orphaned_worker_keys(&mut self, from_index: Option<u32>, limit: Option<u32>) {
+        self.assert_role(Role::Pauser);
+
+        let from_index = from_index.unwrap_or(0) as usize;
+        let limit = limit.unwrap_or(DEFAULT_REVOKE_WORKER_KEYS_LIMIT) as usize;
+        for (public_key, pool_id) in self
+            .orphaned_worker_keys
+            .iter()
+            .skip(from_index)
+            .take(limit)
+        {
+            ext_intents_vault::ext(self.get_pool_account_id(*pool_id))
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_REMOVE_WORKER_KEY)
+                .with_unused_gas_weight(0)
+                .remove_public_key(self.intents_contract_id.clone(), public_key.clone())
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(GAS_REVOKE_WORKER_KEY_CALLBACK)
+                        .with_unused_gas_weight(0)
+                        .on_orphaned_worker_key_revoked(*pool_id, public_key.clone()),
+                );
+        }
+    }
+
+    #[private]
+    pub fn on_worker_key_revoked(
+        &mut self,
+        pool_id: u32,
+        worker_id: AccountId,
+        public_key: PublicKey,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) {
+        if call_result.is_err() {
+            Event::WorkerKeyRevocationFailed {
+                pool_id: &pool_id,
+                public_key: &public_key,
+            }
+            .emit();
+            return;
+        }
+
+        let pool = self.pool_mut(pool_id).expect("Pool not found");
+        if pool.worker_id.as_ref() == Some(&worker_id) {
+            pool.worker_id = None;
+            self.pools.flush();
+        }
+        if let Some(worker) = self.worker_by_account_id.remove(&worker_id) {
+            Event::WorkerRemoved {
+                worker_id: &worker_id,
+                pool_id: &pool_id,
+                public_key: &worker.public_key,
+                compose_hash: &worker.compose_hash,
+                checksum: &worker.checksum,
+            }
+            .emit();
+        }
+    }
+
+    #[private]
+    pub fn on_orphaned_worker_key_revoked(
+        &mut self,
+        pool_id: u32,
+        public_key: PublicKey,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) {
+        if call_result.is_err() {
+            Event::WorkerKeyRevocationFailed {
+                pool_id: &pool_id,
+                public_key: &public_key,
+            }
+            .emit();
+            return;
+        }
+
+        self.orphaned_worker_keys.remove(&public_key);
+        Event::OrphanedWorkerKeyRevoked {
+            pool_id: &pool_id,
+            public_key: &public_key,
+        }
+        .emit();
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_not_paused(&self, feature: PausableFeature) {
+        require!(
+            !self.paused_features.contains(&feature),
+            format!("{:?} is paused", feature)
+        );
+    }
+}
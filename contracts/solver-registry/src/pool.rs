@@ -1,4 +1,4 @@
-use near_sdk::json_types::U128;
+use near_sdk::json_types::{Base58CryptoHash, U128};
 // use near_sdk::json_types::U128;
 use near_sdk::store::LookupMap;
 use near_sdk::{near, require, AccountId, Gas, NearToken, PromiseError, PromiseOrValue};
@@ -7,8 +7,9 @@ use crate::events::Event;
 use crate::ext::ext_ft;
 use crate::*;
 
-const CREATE_POOL_STORAGE_DEPOSIT: NearToken =
-    NearToken::from_yoctonear(1_500_000_000_000_000_000_000_000); // 1.5 NEAR
+// The pool's account uses the intents vault code published as a global contract, so the deposit
+// covers only the account and the pool's state
+const CREATE_POOL_STORAGE_DEPOSIT: NearToken = NearToken::from_millinear(100); // 0.1 NEAR
 const GAS_CREATE_POOL_CALLBACK: Gas = Gas::from_tgas(10);
 
 const ERR_POOL_NOT_FOUND: &str = "Pool not found";
@@ -31,6 +32,13 @@ pub struct Pool {
     pub worker_id: Option<AccountId>,
     /// Last ping timestamp by the pool's worker.
     pub last_ping_timestamp_ms: TimestampMs,
+    /// App compose policy for the pool's workers. Falls back to the global policy if not set.
+    pub app_compose_policy: Option<AppComposePolicy>,
+    /// Sha256 hash of the code of the pool's vault account. Unknown for the vaults created before
+    /// the code hash was tracked.
+    pub vault_code_hash: Option<Base58CryptoHash>,
+    /// Worker ping timeout for the pool. Falls back to the global timeout if not set.
+    pub worker_ping_timeout_ms: Option<TimestampMs>,
 }
 
 #[near(serializers = [json])]
@@ -47,10 +55,19 @@ pub struct PoolInfo {
     pub worker_id: Option<AccountId>,
     /// Last ping timestamp by the pool's worker.
     pub last_ping_timestamp_ms: TimestampMs,
+    /// App compose policy for the pool's workers. Falls back to the global policy if not set.
+    pub app_compose_policy: Option<AppComposePolicy>,
+    /// Sha256 hash of the code of the pool's vault account. Unknown for the vaults created before
+    /// the code hash was tracked.
+    pub vault_code_hash: Option<Base58CryptoHash>,
+    /// Worker ping timeout for the pool. Falls back to the global timeout if not set.
+    pub worker_ping_timeout_ms: Option<TimestampMs>,
+    /// Worker ping timeout in effect for the pool.
+    pub effective_worker_ping_timeout_ms: TimestampMs,
 }
 
 impl Pool {
-    pub fn new(token_ids: Vec<AccountId>, fee: u32) -> Self {
+    pub fn new(token_ids: Vec<AccountId>, fee: u32, vault_code_hash: Base58CryptoHash) -> Self {
         require!(token_ids.len() == 2, "Must have exactly 2 tokens");
         require!(
             token_ids[0] != token_ids[1],
@@ -66,12 +83,24 @@ impl Pool {
             shares_total_supply: 0,
             worker_id: None,
             last_ping_timestamp_ms: 0,
+            app_compose_policy: None,
+            vault_code_hash: Some(vault_code_hash),
+            worker_ping_timeout_ms: None,
         }
     }
 
-    /// Assume the worker is active if there's a ping within the timeout period.
-    pub fn has_active_worker(&self, timeout_ms: TimestampMs) -> bool {
-        self.worker_id.is_some() && block_timestamp_ms() < self.last_ping_timestamp_ms + timeout_ms
+    /// Worker ping timeout of the pool, or the given default timeout if not set.
+    pub fn effective_worker_ping_timeout_ms(&self, default_timeout_ms: TimestampMs) -> TimestampMs {
+        self.worker_ping_timeout_ms.unwrap_or(default_timeout_ms)
+    }
+
+    /// Assume the worker is active if there's a ping within the pool's timeout period, or the
+    /// given default timeout if the pool doesn't override it.
+    pub fn has_active_worker(&self, default_timeout_ms: TimestampMs) -> bool {
+        self.worker_id.is_some()
+            && block_timestamp_ms()
+                < self.last_ping_timestamp_ms
+                    + self.effective_worker_ping_timeout_ms(default_timeout_ms)
     }
 }
 
@@ -84,11 +113,14 @@ impl Contract {
         token_ids: Vec<AccountId>,
         fee: u32,
     ) -> PromiseOrValue<Option<u32>> {
+        self.assert_not_paused(PausableFeature::CreatePool);
         require!(
             env::attached_deposit() >= CREATE_POOL_STORAGE_DEPOSIT,
             "Not enough attached deposit"
         );
 
+        let vault_code_hash = self.published_intents_vault_code_hash();
+
         // Get new pool ID
         let pool_id = self.pools.len();
 
@@ -97,11 +129,11 @@ impl Contract {
         Promise::new(pool_account_id)
             .create_account()
             .transfer(CREATE_POOL_STORAGE_DEPOSIT)
-            .deploy_contract(include_bytes!("../../intents-vault/res/intents_vault.wasm").to_vec())
+            .use_global_contract(vault_code_hash)
             .then(
                 Self::ext(env::current_account_id())
                     .with_static_gas(GAS_CREATE_POOL_CALLBACK)
-                    .on_create_liquidity_pool_account(pool_id, token_ids, fee),
+                    .on_create_liquidity_pool_account(pool_id, token_ids, fee, vault_code_hash),
             )
             .into()
     }
@@ -112,13 +144,14 @@ impl Contract {
         pool_id: u32,
         token_ids: Vec<AccountId>,
         fee: u32,
+        vault_code_hash: Base58CryptoHash,
         #[callback_result] call_result: Result<(), PromiseError>,
     ) -> Option<u32> {
         if call_result.is_err() {
             None
         } else {
             // Add the new liquidity pool
-            let pool = Pool::new(token_ids.clone(), fee);
+            let pool = Pool::new(token_ids.clone(), fee, vault_code_hash);
             self.pools.push(pool);
             self.pools.flush();
 
@@ -150,6 +183,17 @@ impl Contract {
 }
 
 impl Contract {
+    /// Get the pool, which must be migrated to the current layout
+    pub(crate) fn pool(&self, pool_id: u32) -> Option<&Pool> {
+        self.assert_pool_migrated(pool_id);
+        self.pools.get(pool_id)
+    }
+
+    pub(crate) fn pool_mut(&mut self, pool_id: u32) -> Option<&mut Pool> {
+        self.assert_pool_migrated(pool_id);
+        self.pools.get_mut(pool_id)
+    }
+
     pub(crate) fn get_pool_account_id(&self, pool_id: u32) -> AccountId {
         format!("pool-{}.{}", pool_id, env::current_account_id())
             .parse()
@@ -163,7 +207,7 @@ impl Contract {
         _sender_id: &AccountId,
         amount: Balance,
     ) -> PromiseOrValue<U128> {
-        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        let pool = self.pool(pool_id).expect(ERR_POOL_NOT_FOUND);
 
         require!(pool.token_ids.contains(token_id), ERR_BAD_TOKEN_ID);
         require!(amount > 0, ERR_INVALID_AMOUNT);
@@ -0,0 +1,59 @@
+use near_sdk::near;
+
+use crate::*;
+
+#[near]
+impl Contract {
+    /// Grant a role to the account (owner only)
+    pub fn grant_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_owner();
+
+        let members = self
+            .role_members
+            .entry(role)
+            .or_insert_with(|| IterableSet::new(Prefix::RoleMembers { role }));
+        require!(members.insert(account_id.clone()), "Role already granted");
+
+        Event::RoleGranted {
+            role: &role,
+            account_id: &account_id,
+        }
+        .emit();
+    }
+
+    /// Revoke a role from the account (owner only)
+    pub fn revoke_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_owner();
+
+        require!(
+            self.role_members
+                .get_mut(&role)
+                .is_some_and(|members| members.remove(&account_id)),
+            "Role not granted"
+        );
+
+        Event::RoleRevoked {
+            role: &role,
+            account_id: &account_id,
+        }
+        .emit();
+    }
+}
+
+impl Contract {
+    /// Whether the account is the owner or has been granted the role.
+    pub(crate) fn is_owner_or_has_role(&self, role: Role, account_id: &AccountId) -> bool {
+        account_id == &self.owner_id
+            || self
+                .role_members
+                .get(&role)
+                .is_some_and(|members| members.contains(account_id))
+    }
+
+    pub(crate) fn assert_role(&self, role: Role) {
+        require!(
+            self.is_owner_or_has_role(role, &env::predecessor_account_id()),
+            format!("Missing role {:?}", role)
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use near_sdk::{json_types::Base58CryptoHash, near};
+
+use crate::*;
+
+#[near]
+impl Contract {
+    /// Set the delay before scheduled sensitive admin actions can be executed. Scheduled with the
+    /// current delay if set, so the delay can't be shortened without notice.
+    pub fn set_timelock_delay_ms(&mut self, timelock_delay_ms: TimestampMs) -> Option<ActionId> {
+        self.assert_owner();
+
+        self.schedule_or_execute(TimelockAction::SetTimelockDelay { timelock_delay_ms })
+    }
+
+    /// Execute a scheduled action after its timelock delay. Upgrades are executed with `upgrade`.
+    pub fn execute_action(&mut self, action_id: ActionId) {
+        let scheduled_action = self
+            .scheduled_actions
+            .get(&action_id)
+            .expect("Action not found");
+        require!(
+            !matches!(scheduled_action.action, TimelockAction::Upgrade { .. }),
+            "Upgrade must be executed with `upgrade`"
+        );
+
+        let action = self.take_executable_action(action_id);
+        self.apply_timelock_action(action);
+    }
+
+    /// Cancel a scheduled action
+    pub fn cancel_action(&mut self, action_id: ActionId) {
+        let scheduled_action = self
+            .scheduled_actions
+            .remove(&action_id)
+            .expect("Action not found");
+        self.assert_action_role(&scheduled_action.action);
+
+        Event::ActionCancelled {
+            action_id: &action_id,
+            action: &scheduled_action.action,
+        }
+        .emit();
+    }
+}
+
+impl Contract {
+    /// Schedules the action if the timelock delay is set and returns its ID, otherwise executes
+    /// the action immediately.
+    pub(crate) fn schedule_or_execute(&mut self, action: TimelockAction) -> Option<ActionId> {
+        if self.timelock_delay_ms == 0 {
+            self.apply_timelock_action(action);
+            return None;
+        }
+
+        Some(self.schedule_action(action))
+    }
+
+    pub(crate) fn schedule_action(&mut self, action: TimelockAction) -> ActionId {
+        let action_id = self.next_action_id;
+        self.next_action_id += 1;
+
+        let scheduled_action = ScheduledAction {
+            action,
+            scheduled_by: env::predecessor_account_id(),
+            scheduled_at_ms: block_timestamp_ms(),
+            executable_at_ms: block_timestamp_ms() + self.timelock_delay_ms,
+        };
+
+        Event::ActionScheduled {
+            action_id: &action_id,
+            action: &scheduled_action.action,
+            executable_at_ms: &scheduled_action.executable_at_ms,
+        }
+        .emit();
+
+        self.scheduled_actions.insert(action_id, scheduled_action);
+        action_id
+    }
+
+    /// Removes the scheduled action if its timelock delay has passed and the caller is allowed to
+    /// execute it.
+    pub(crate) fn take_executable_action(&mut self, action_id: ActionId) -> TimelockAction {
+        let scheduled_action = self
+            .scheduled_actions
+            .remove(&action_id)
+            .expect("Action not found");
+        self.assert_action_role(&scheduled_action.action);
+        require!(
+            block_timestamp_ms() >= scheduled_action.executable_at_ms,
+            "Timelock delay not passed"
+        );
+
+        Event::ActionExecuted {
+            action_id: &action_id,
+            action: &scheduled_action.action,
+        }
+        .emit();
+
+        scheduled_action.action
+    }
+
+    /// Finds the scheduled upgrade to the code with the hash.
+    pub(crate) fn find_scheduled_upgrade(&self, code_hash: &Base58CryptoHash) -> Option<ActionId> {
+        self.scheduled_actions
+            .iter()
+            .find(|(_, scheduled_action)| {
+                matches!(
+                    &scheduled_action.action,
+                    TimelockAction::Upgrade { code_hash: hash } if hash == code_hash
+                )
+            })
+            .map(|(action_id, _)| *action_id)
+    }
+
+    fn assert_action_role(&self, action: &TimelockAction) {
+        match action.role() {
+            Some(role) => self.assert_role(role),
+            None => self.assert_owner(),
+        }
+    }
+
+    fn apply_timelock_action(&mut self, action: TimelockAction) {
+        match action {
+            TimelockAction::ApproveComposeHash { compose_hash } => {
+                self.assert_multisig_disabled();
+                self.apply_approve_compose_hash(compose_hash)
+            }
+            TimelockAction::ApprovePreLaunchScript {
+                compose_hash,
+                pre_launch_script_hash,
+            } => {
+                self.assert_multisig_disabled();
+                self.apply_approve_pre_launch_script(compose_hash, pre_launch_script_hash)
+            }
+            TimelockAction::RemovePreLaunchScript {
+                compose_hash,
+                pre_launch_script_hash,
+            } => {
+                self.assert_multisig_disabled();
+                self.apply_remove_pre_launch_script(compose_hash, pre_launch_script_hash)
+            }
+            TimelockAction::ApproveDockerImageHash {
+                compose_hash,
+                docker_image_hash,
+            } => {
+                self.assert_multisig_disabled();
+                self.apply_approve_docker_image_hash(compose_hash, docker_image_hash)
+            }
+            TimelockAction::RemoveDockerImageHash {
+                compose_hash,
+                docker_image_hash,
+            } => {
+                self.assert_multisig_disabled();
+                self.apply_remove_docker_image_hash(compose_hash, docker_image_hash)
+            }
+            TimelockAction::SetComposeHashAllowedEnvs {
+                compose_hash,
+                allowed_envs,
+            } => {
+                self.assert_multisig_disabled();
+                self.apply_set_compose_hash_allowed_envs(compose_hash, allowed_envs)
+            }
+            TimelockAction::SetAppComposePolicy { app_compose_policy } => {
+                self.apply_set_app_compose_policy(app_compose_policy)
+            }
+            TimelockAction::SetPoolAppComposePolicy {
+                pool_id,
+                app_compose_policy,
+            } => self.apply_set_pool_app_compose_policy(pool_id, app_compose_policy),
+            TimelockAction::SetAllowLegacyReportData {
+                allow_legacy_report_data,
+            } => self.apply_set_allow_legacy_report_data(allow_legacy_report_data),
+            TimelockAction::SetAllowedKeyTypes { allowed_key_types } => {
+                self.apply_set_allowed_key_types(allowed_key_types)
+            }
+            TimelockAction::SetMaxCollateralAge {
+                max_collateral_age_ms,
+            } => self.apply_set_max_collateral_age(max_collateral_age_ms),
+            TimelockAction::SetDockerImageHashEvent {
+                docker_image_hash_event,
+            } => self.apply_set_docker_image_hash_event(docker_image_hash_event),
+            TimelockAction::SetTcbPolicy { tcb_policy } => self.apply_set_tcb_policy(tcb_policy),
+            TimelockAction::Upgrade { .. } => {
+                env::panic_str("Upgrade must be executed with `upgrade`")
+            }
+            TimelockAction::SetTimelockDelay { timelock_delay_ms } => {
+                self.timelock_delay_ms = timelock_delay_ms;
+
+                Event::TimelockDelayUpdated {
+                    timelock_delay_ms: &self.timelock_delay_ms,
+                }
+                .emit();
+            }
+            TimelockAction::SetGuardians { guardian_set } => self.apply_set_guardians(guardian_set),
+        }
+    }
+}
@@ -23,6 +23,7 @@ impl Contract {
             return PromiseOrValue::Value(amount);
         }
 
+        self.assert_not_paused(PausableFeature::Deposit);
         let token_id = env::predecessor_account_id();
         let message =
             serde_json::from_str::<TokenReceiverMessage>(&msg).expect(ERR_MALFORMED_MESSAGE);
@@ -1,7 +1,12 @@
-use near_sdk::{near, BorshStorageKey};
+use near_sdk::{
+    json_types::Base58CryptoHash, near, AccountId, BorshStorageKey, CurveType, PublicKey,
+};
+
+use crate::attestation::{app_compose_policy::AppComposePolicy, tcb_policy::TcbPolicy};
 
 pub type Balance = u128;
 pub type TimestampMs = u64;
+pub type ActionId = u64;
 
 #[near]
 #[derive(BorshStorageKey)]
@@ -10,4 +15,255 @@ pub enum Prefix {
     PoolShares,
     ApprovedComposeHashes,
     WorkerByAccountId,
+    ComposeHashConfigs,
+    RegistrationChallenges,
+    CachedCollaterals,
+    Roles,
+    RoleMembers { role: Role },
+    ScheduledActions,
+    ComposeHashVotes,
+    Workers,
+    OrphanedWorkerKeys,
+    ActionVotes,
+}
+
+/// Features which can be paused by the pauser in an emergency.
+#[near(serializers = [json, borsh])]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PausableFeature {
+    /// Worker registration and key rotation
+    RegisterWorker,
+    /// Worker pings
+    Ping,
+    /// Deposits into pools with `ft_on_transfer`
+    Deposit,
+    /// Liquidity pool creation
+    CreatePool,
+}
+
+/// Administrative roles granted by the owner. The owner implicitly holds all roles.
+#[near(serializers = [json, borsh])]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    /// Approves and removes docker compose hashes and their pre-launch scripts, docker images and
+    /// allowed environment variables
+    ComposeHashManager,
+    /// Manages the configuration of liquidity pools
+    PoolManager,
+    /// Pauses and unpauses the registry
+    Pauser,
+    /// Upgrades the registry contract
+    Upgrader,
+}
+
+/// Sensitive admin action which is executed only after the timelock delay.
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimelockAction {
+    ApproveComposeHash {
+        compose_hash: String,
+    },
+    ApprovePreLaunchScript {
+        compose_hash: String,
+        pre_launch_script_hash: String,
+    },
+    /// Remove a pre-launch script, executed directly or voted by the guardians in the multisig
+    /// mode
+    RemovePreLaunchScript {
+        compose_hash: String,
+        pre_launch_script_hash: String,
+    },
+    ApproveDockerImageHash {
+        compose_hash: String,
+        docker_image_hash: String,
+    },
+    RemoveDockerImageHash {
+        compose_hash: String,
+        docker_image_hash: String,
+    },
+    SetComposeHashAllowedEnvs {
+        compose_hash: String,
+        allowed_envs: Option<Vec<String>>,
+    },
+    SetAppComposePolicy {
+        app_compose_policy: AppComposePolicy,
+    },
+    SetPoolAppComposePolicy {
+        pool_id: u32,
+        app_compose_policy: Option<AppComposePolicy>,
+    },
+    SetAllowLegacyReportData {
+        allow_legacy_report_data: bool,
+    },
+    SetAllowedKeyTypes {
+        allowed_key_types: Vec<KeyType>,
+    },
+    SetMaxCollateralAge {
+        max_collateral_age_ms: TimestampMs,
+    },
+    SetDockerImageHashEvent {
+        docker_image_hash_event: Option<String>,
+    },
+    SetTcbPolicy {
+        tcb_policy: TcbPolicy,
+    },
+    /// Upgrade the contract to the code with the sha256 hash, staged with `stage_upgrade` and
+    /// executed with `upgrade`
+    Upgrade {
+        code_hash: Base58CryptoHash,
+    },
+    SetTimelockDelay {
+        timelock_delay_ms: TimestampMs,
+    },
+    /// Set the guardians voting on compose hash changes. Disables the multisig mode if `None`.
+    SetGuardians {
+        guardian_set: Option<GuardianSet>,
+    },
+}
+
+impl TimelockAction {
+    /// Role allowed to schedule, execute and cancel the action besides the owner. Owner only if
+    /// `None`.
+    pub fn role(&self) -> Option<Role> {
+        match self {
+            Self::ApproveComposeHash { .. }
+            | Self::ApprovePreLaunchScript { .. }
+            | Self::RemovePreLaunchScript { .. }
+            | Self::ApproveDockerImageHash { .. }
+            | Self::RemoveDockerImageHash { .. }
+            | Self::SetComposeHashAllowedEnvs { .. } => Some(Role::ComposeHashManager),
+            Self::SetPoolAppComposePolicy { .. } => Some(Role::PoolManager),
+            Self::Upgrade { .. } => Some(Role::Upgrader),
+            Self::SetAppComposePolicy { .. }
+            | Self::SetAllowLegacyReportData { .. }
+            | Self::SetAllowedKeyTypes { .. }
+            | Self::SetMaxCollateralAge { .. }
+            | Self::SetDockerImageHashEvent { .. }
+            | Self::SetTcbPolicy { .. }
+            | Self::SetTimelockDelay { .. }
+            | Self::SetGuardians { .. } => None,
+        }
+    }
+}
+
+/// An action scheduled in the timelock queue.
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Debug)]
+pub struct ScheduledAction {
+    pub action: TimelockAction,
+    pub scheduled_by: AccountId,
+    pub scheduled_at_ms: TimestampMs,
+    /// The action can't be executed before this time
+    pub executable_at_ms: TimestampMs,
+}
+
+/// Guardians whose votes are required to approve or remove compose hashes in the multisig mode.
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GuardianSet {
+    pub guardians: Vec<AccountId>,
+    /// Number of guardian votes required for a compose hash change
+    pub threshold: u32,
+}
+
+/// Pending guardian votes on a compose hash.
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Debug, Default)]
+pub struct ComposeHashVotes {
+    /// Guardians who voted to approve the compose hash
+    pub approvals: Vec<AccountId>,
+    /// Guardians who voted to remove the compose hash
+    pub removals: Vec<AccountId>,
+}
+
+/// Pending guardian votes on an action requiring guardian votes in the multisig mode.
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Debug)]
+pub struct ActionVotes {
+    pub action: TimelockAction,
+    /// Guardians who voted for the action
+    pub voters: Vec<AccountId>,
+}
+
+/// Curve type of a worker's intents public key
+#[near(serializers = [json, borsh])]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
+    Ed25519,
+    Secp256k1,
+}
+
+impl From<&PublicKey> for KeyType {
+    fn from(public_key: &PublicKey) -> Self {
+        match public_key.curve_type() {
+            CurveType::ED25519 => KeyType::Ed25519,
+            CurveType::SECP256K1 => KeyType::Secp256k1,
+        }
+    }
+}
+
+/// A nonce issued by the registry for a worker to include in the report data of its attestation.
+/// The challenge is consumed by the worker's registration and must be used before it expires.
+#[near(serializers = [json, borsh])]
+#[derive(Clone)]
+pub struct RegistrationChallenge {
+    pub pool_id: u32,
+    /// Hex-encoded 32-byte nonce
+    pub nonce: String,
+    pub expires_at_ms: TimestampMs,
+}
+
+/// Validity of a cached collateral, from the oldest issue date to the earliest next update of
+/// its TCB info and QE identity.
+#[near(serializers = [json])]
+pub struct CollateralValidity {
+    pub issue_date_ms: TimestampMs,
+    pub next_update_ms: TimestampMs,
+}
+
+/// NEP-330 source metadata of the contract code, set by `cargo near build` from the reproducible
+/// build config in `Cargo.toml`. Allows matching the deployed code against its source.
+#[near(serializers = [json])]
+#[derive(Clone, Debug)]
+pub struct SourceMetadata {
+    pub version: Option<String>,
+    pub link: Option<String>,
+    /// Docker image of the reproducible build
+    pub build_environment: Option<String>,
+    pub build_command: Option<String>,
+    /// Source code snapshot the code was built from, e.g. a git commit
+    pub source_code_snapshot: Option<String>,
+}
+
+impl SourceMetadata {
+    /// Source metadata of the code being executed
+    pub fn current() -> Self {
+        Self {
+            version: Some(option_env!("NEP330_VERSION").unwrap_or(env!("CARGO_PKG_VERSION")))
+                .map(str::to_string),
+            link: option_env!("NEP330_LINK")
+                .or(option_env!("CARGO_PKG_REPOSITORY"))
+                .map(str::to_string),
+            build_environment: option_env!("NEP330_BUILD_INFO_BUILD_ENVIRONMENT")
+                .map(str::to_string),
+            build_command: option_env!("NEP330_BUILD_INFO_BUILD_COMMAND").map(str::to_string),
+            source_code_snapshot: option_env!("NEP330_BUILD_INFO_SOURCE_CODE_SNAPSHOT")
+                .map(str::to_string),
+        }
+    }
+}
+
+/// Configuration bound to an approved docker compose hash.
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Default)]
+pub struct ComposeHashConfig {
+    /// Hex-encoded sha256 hashes of the pre-launch scripts approved to run with the docker
+    /// compose file. A CVM with a pre-launch script must run one of these scripts.
+    pub pre_launch_script_hashes: Vec<String>,
+    /// Allowlist of environment variable names which can be passed to the CVM. Overrides the
+    /// `allowed_envs` of the app compose policy if set.
+    pub allowed_envs: Option<Vec<String>>,
+    /// Hex-encoded sha256 digests of the docker images approved to run with the docker compose
+    /// file. If not empty, the CVM must measure one of them in the docker image hash event.
+    pub docker_image_hashes: Vec<String>,
 }
@@ -1,10 +1,36 @@
-use crate::{Contract, ContractExt};
+use crate::legacy::{ContractV0, PoolV0, WorkerV0};
+use crate::*;
 
+use near_sdk::serde_json::json;
 use near_sdk::{
-    assert_one_yocto, env, near_bindgen, AccountId, Gas, GasWeight, NearToken, Promise,
-    PromiseOrValue,
+    assert_one_yocto, borsh, env, json_types::Base58CryptoHash, near_bindgen, AccountId, Gas,
+    GasWeight, NearToken, Promise, PromiseOrValue,
 };
 
+/// Version of the current storage layout of `Contract`, `Pool` and `Worker`. Bump it on any
+/// layout change of a released version and add the migration from the previous version to
+/// `migrate`, keeping the previous layout in `legacy.rs`.
+pub const STATE_VERSION: u32 = 1;
+
+/// Storage key of the state version, stored apart from the state so it can be read before
+/// knowing the layout. The state of version 0 was deployed without it.
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+/// Number of pools and workers migrated by `migrate`, and by `migrate_pools` by default
+const DEFAULT_MIGRATE_POOLS_LIMIT: u32 = 10;
+
+/// Pools and workers of the previous layout which remain to be rewritten by `migrate_pools`. The
+/// pools from `next_pool_id` until the end of `pools_v0` can't be accessed until migrated.
+#[near(serializers = [borsh])]
+pub struct PendingMigration {
+    pools_v0: Vector<PoolV0>,
+    workers_v0: IterableMap<AccountId, WorkerV0>,
+    next_pool_id: u32,
+    /// Validity of the attestations of the migrated workers, which were registered without the
+    /// collateral dates
+    attestation_valid_until_ms: TimestampMs,
+}
+
 #[near_bindgen]
 impl Contract {
     #[init(ignore_state)]
@@ -12,12 +38,83 @@ impl Contract {
     #[private]
     pub fn migrate() -> Self {
         assert_one_yocto();
-        env::state_read::<Self>().expect("Failed to read contract state")
+        let from_version = Self::read_state_version();
+        require!(
+            from_version <= STATE_VERSION,
+            format!("Unknown state version {}", from_version)
+        );
+
+        // Each migration rewrites the state of a version into the layout of the next one
+        for version in from_version..STATE_VERSION {
+            match version {
+                0 => migrate_v0_to_v1(),
+                _ => env::panic_str(&format!("No migration from state version {}", version)),
+            }
+        }
+
+        if from_version != STATE_VERSION {
+            Self::write_state_version();
+
+            Event::StateMigrated {
+                from_version: &from_version,
+                to_version: &STATE_VERSION,
+            }
+            .emit();
+        }
+
+        // The first pools are migrated with the upgrade, the rest with `migrate_pools`
+        let mut contract = env::state_read::<Self>().expect("Failed to read contract state");
+        contract.migrate_pending_pools(DEFAULT_MIGRATE_POOLS_LIMIT);
+        contract
+    }
+
+    /// Migrate the next pools and workers left by the state migration of the upgrade. Returns
+    /// whether all pools and workers are migrated.
+    pub fn migrate_pools(&mut self, limit: Option<u32>) -> bool {
+        self.assert_role(Role::Upgrader);
+        require!(self.pending_migration.is_some(), "No pending migration");
+
+        self.migrate_pending_pools(limit.unwrap_or(DEFAULT_MIGRATE_POOLS_LIMIT))
     }
 
+    /// Emits the hash and the source metadata of the new code after an upgrade
+    #[private]
+    pub fn on_upgraded(&self, code_hash: Base58CryptoHash) {
+        Event::ContractUpgraded {
+            code_hash: &code_hash,
+            source_metadata: &SourceMetadata::current(),
+        }
+        .emit();
+    }
+
+    /// Stage the upgrade to the code with the sha256 hash. The upgrade can be executed with
+    /// `upgrade` after the timelock delay, and cancelled with `cancel_action`.
+    pub fn stage_upgrade(&mut self, code_hash: Base58CryptoHash) -> ActionId {
+        self.assert_role(Role::Upgrader);
+        require!(
+            self.find_scheduled_upgrade(&code_hash).is_none(),
+            "Upgrade already staged"
+        );
+
+        self.schedule_action(TimelockAction::Upgrade { code_hash })
+    }
+
+    /// Upgrade the contract to the code passed as the input. The hash of the code must match a
+    /// staged upgrade whose timelock delay has passed, and the pools of the previous upgrade must
+    /// be migrated.
     pub fn upgrade(&mut self) -> PromiseOrValue<AccountId> {
-        self.assert_owner();
+        self.assert_role(Role::Upgrader);
+        require!(
+            self.pending_migration.is_none(),
+            "Pools of the previous upgrade not migrated"
+        );
         let code = env::input().expect("Code not found");
+        let code_hash = Base58CryptoHash::from(env::sha256_array(&code));
+        let action_id = self
+            .find_scheduled_upgrade(&code_hash)
+            .expect("Upgrade not staged for the code hash");
+        self.take_executable_action(action_id);
+
         Promise::new(env::current_account_id())
             .deploy_contract(code)
             .function_call_weight(
@@ -27,6 +124,13 @@ impl Contract {
                 Gas::from_tgas(0),
                 GasWeight(1),
             )
+            .function_call_weight(
+                "on_upgraded".into(),
+                json!({ "code_hash": code_hash }).to_string().into_bytes(),
+                NearToken::from_yoctonear(0),
+                Gas::from_tgas(5),
+                GasWeight(0),
+            )
             .function_call_weight(
                 "get_owner_id".into(),
                 vec![],
@@ -37,3 +141,177 @@ impl Contract {
             .into()
     }
 }
+
+impl Contract {
+    pub(crate) fn read_state_version() -> u32 {
+        env::storage_read(STATE_VERSION_KEY)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().expect("Invalid state version")))
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn write_state_version() {
+        env::storage_write(STATE_VERSION_KEY, &STATE_VERSION.to_le_bytes());
+    }
+
+    pub(crate) fn assert_pool_migrated(&self, pool_id: u32) {
+        if let Some(migration) = &self.pending_migration {
+            require!(
+                pool_id < migration.next_pool_id || pool_id >= migration.pools_v0.len(),
+                "Pool not migrated"
+            );
+        }
+    }
+
+    /// Rewrites up to `limit` pools of the pending migration along with their workers, then the
+    /// workers of no pool. Returns whether the migration is complete.
+    fn migrate_pending_pools(&mut self, limit: u32) -> bool {
+        let Some(mut migration) = self.pending_migration.take() else {
+            return true;
+        };
+
+        let from_pool_id = migration.next_pool_id;
+        let mut remaining = limit;
+        while remaining > 0 && migration.next_pool_id < migration.pools_v0.len() {
+            let pool_id = migration.next_pool_id;
+            let pool = read_pool_v0(&migration.pools_v0, pool_id);
+            if let Some(worker_id) = pool.worker_id.clone() {
+                if let Some(worker) = migration.workers_v0.remove(&worker_id) {
+                    self.worker_by_account_id.insert(
+                        worker_id,
+                        migrate_worker(worker, migration.attestation_valid_until_ms),
+                    );
+                }
+            }
+            self.pools.set(pool_id, migrate_pool(pool));
+            migration.next_pool_id += 1;
+            remaining -= 1;
+        }
+        while remaining > 0 {
+            let Some(worker_id) = migration.workers_v0.keys().next().cloned() else {
+                break;
+            };
+            let worker = migration
+                .workers_v0
+                .remove(&worker_id)
+                .expect("Worker not registered");
+            self.worker_by_account_id.insert(
+                worker_id,
+                migrate_worker(worker, migration.attestation_valid_until_ms),
+            );
+            remaining -= 1;
+        }
+        self.pools.flush();
+        self.worker_by_account_id.flush();
+        migration.workers_v0.flush();
+
+        Event::PoolsMigrated {
+            from_pool_id: &from_pool_id,
+            to_pool_id: &migration.next_pool_id,
+        }
+        .emit();
+
+        let complete =
+            migration.next_pool_id == migration.pools_v0.len() && migration.workers_v0.is_empty();
+        if !complete {
+            self.pending_migration = Some(migration);
+        }
+        complete
+    }
+}
+
+/// Migrates from the initial layout. Only the contract state is rewritten here, the pools and
+/// workers are rewritten in batches by `migrate_pools`. Legacy report data stays allowed so that
+/// the registered workers keep working until the owner disables it.
+fn migrate_v0_to_v1() {
+    let ContractV0 {
+        owner_id,
+        intents_contract_id,
+        pools: pools_v0,
+        approved_compose_hashes,
+        worker_by_account_id: workers_v0,
+        worker_ping_timeout_ms,
+    } = env::state_read().expect("Failed to read contract state");
+
+    // The pools keep their storage keys, so the vector of the current layout is the same
+    // vector read with the new pool type
+    let pools: Vector<Pool> =
+        borsh::from_slice(&borsh::to_vec(&pools_v0).expect("Failed to serialize pools"))
+            .expect("Failed to deserialize pools");
+    let pending_migration =
+        (!pools_v0.is_empty() || !workers_v0.is_empty()).then(|| PendingMigration {
+            pools_v0,
+            workers_v0,
+            next_pool_id: 0,
+            // The workers registered without the collateral dates must refresh their attestation
+            // within the maximum collateral age
+            attestation_valid_until_ms: attestation_timestamp_ms()
+                .saturating_add(DEFAULT_MAX_COLLATERAL_AGE_MS),
+        });
+
+    env::state_write(&Contract {
+        owner_id,
+        pending_owner_id: None,
+        intents_contract_id,
+        pools,
+        approved_compose_hashes,
+        compose_hash_configs: LookupMap::new(Prefix::ComposeHashConfigs),
+        worker_by_account_id: IterableMap::new(Prefix::Workers),
+        worker_ping_timeout_ms,
+        tcb_policy: TcbPolicy::default(),
+        app_compose_policy: AppComposePolicy::default(),
+        docker_image_hash_event: None,
+        registration_challenges: LookupMap::new(Prefix::RegistrationChallenges),
+        registration_challenge_ttl_ms: DEFAULT_REGISTRATION_CHALLENGE_TTL_MS,
+        allow_legacy_report_data: true,
+        allowed_key_types: vec![KeyType::Ed25519, KeyType::Secp256k1],
+        max_collateral_age_ms: DEFAULT_MAX_COLLATERAL_AGE_MS,
+        cached_collaterals: LookupMap::new(Prefix::CachedCollaterals),
+        role_members: LookupMap::new(Prefix::Roles),
+        timelock_delay_ms: 0,
+        scheduled_actions: IterableMap::new(Prefix::ScheduledActions),
+        next_action_id: 0,
+        guardian_set: None,
+        compose_hash_votes: IterableMap::new(Prefix::ComposeHashVotes),
+        action_votes: IterableMap::new(Prefix::ActionVotes),
+        paused_features: vec![],
+        orphaned_worker_keys: IterableMap::new(Prefix::OrphanedWorkerKeys),
+        intents_vault_code_hash: None,
+        pending_migration,
+    });
+}
+
+/// Reads the pool of the previous layout by value, since its storage is overwritten by the
+/// migrated pool
+fn read_pool_v0(pools_v0: &Vector<PoolV0>, pool_id: u32) -> PoolV0 {
+    let pool = pools_v0.get(pool_id).expect("Pool not found");
+    borsh::from_slice(&borsh::to_vec(pool).expect("Failed to serialize pool"))
+        .expect("Failed to deserialize pool")
+}
+
+fn migrate_pool(pool: PoolV0) -> Pool {
+    Pool {
+        token_ids: pool.token_ids,
+        amounts: pool.amounts,
+        fee: pool.fee,
+        shares: pool.shares,
+        shares_total_supply: pool.shares_total_supply,
+        worker_id: pool.worker_id,
+        last_ping_timestamp_ms: pool.last_ping_timestamp_ms,
+        app_compose_policy: None,
+        vault_code_hash: None,
+        worker_ping_timeout_ms: None,
+    }
+}
+
+fn migrate_worker(worker: WorkerV0, attestation_valid_until_ms: TimestampMs) -> Worker {
+    Worker {
+        pool_id: worker.pool_id,
+        checksum: worker.checksum,
+        compose_hash: worker.compose_hash,
+        public_key: worker.public_key,
+        // Only the "UpToDate" TCB status was accepted before
+        tcb_status: "UpToDate".to_string(),
+        allowed_envs: vec![],
+        attestation_valid_until_ms,
+    }
+}
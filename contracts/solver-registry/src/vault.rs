@@ -0,0 +1,164 @@
+use near_sdk::{json_types::Base58CryptoHash, log, near, Gas, PromiseError};
+
+use crate::*;
+
+/// Code of the intents vault published as a global contract for the pools' accounts
+pub(crate) const INTENTS_VAULT_WASM: &[u8] =
+    include_bytes!("../../intents-vault/res/intents_vault.wasm");
+
+/// Cost of the storage of global contracts per byte, 10 times the cost of the regular storage
+const GLOBAL_CONTRACT_STORAGE_COST_PER_BYTE: NearToken =
+    NearToken::from_yoctonear(100_000_000_000_000_000_000);
+
+const GAS_PUBLISH_INTENTS_VAULT_CODE_CALLBACK: Gas = Gas::from_tgas(10);
+const GAS_UPGRADE_POOL_VAULT: Gas = Gas::from_tgas(20);
+const GAS_UPGRADE_POOL_VAULT_CALLBACK: Gas = Gas::from_tgas(10);
+const DEFAULT_UPGRADE_POOL_VAULTS_LIMIT: u32 = 10;
+
+#[near]
+impl Contract {
+    /// Publish the intents vault code bundled with the registry as a global contract, which the
+    /// accounts of new pools use by its code hash (Upgrader). The attached deposit must cover the
+    /// storage of the global contract. The unused deposit is refunded.
+    #[payable]
+    pub fn publish_intents_vault_code(&mut self) -> Promise {
+        self.assert_role(Role::Upgrader);
+        let code_hash = bundled_intents_vault_code_hash();
+        require!(
+            self.intents_vault_code_hash != Some(code_hash),
+            "Intents vault code already published"
+        );
+
+        let storage_cost =
+            GLOBAL_CONTRACT_STORAGE_COST_PER_BYTE.saturating_mul(INTENTS_VAULT_WASM.len() as u128);
+        let attached_deposit = env::attached_deposit();
+        require!(
+            attached_deposit >= storage_cost,
+            format!(
+                "Not enough attached deposit, {} required for storage",
+                storage_cost.exact_amount_display()
+            )
+        );
+        let publisher_id = env::predecessor_account_id();
+        let refund = attached_deposit.saturating_sub(storage_cost);
+        if !refund.is_zero() {
+            Promise::new(publisher_id.clone()).transfer(refund);
+        }
+
+        Promise::new(env::current_account_id())
+            .deploy_global_contract(INTENTS_VAULT_WASM.to_vec())
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_PUBLISH_INTENTS_VAULT_CODE_CALLBACK)
+                    .on_intents_vault_code_published(code_hash, publisher_id, storage_cost),
+            )
+    }
+
+    /// Upgrade the vault of the pool to the published intents vault code (Upgrader). The vaults
+    /// created before the vault upgrades were supported, including all the pools migrated from the
+    /// state version 0, have no code hash and stay non-upgradeable.
+    pub fn upgrade_pool_vault(&mut self, pool_id: u32) -> Promise {
+        self.assert_role(Role::Upgrader);
+        let pool = self.pool(pool_id).expect("Pool not found");
+        require!(
+            pool.vault_code_hash.is_some(),
+            "Pool vault has no code hash and can't be upgraded"
+        );
+
+        self.upgrade_vault(pool_id, self.published_intents_vault_code_hash())
+    }
+
+    /// Upgrade the vaults of up to `limit` pools from `from_index` which don't use the published
+    /// intents vault code (Upgrader). The vaults with no code hash are skipped.
+    pub fn upgrade_all_pool_vaults(&mut self, from_index: Option<u32>, limit: Option<u32>) {
+        self.assert_role(Role::Upgrader);
+
+        let code_hash = self.published_intents_vault_code_hash();
+        let from_index = from_index.unwrap_or(0);
+        let limit = limit.unwrap_or(DEFAULT_UPGRADE_POOL_VAULTS_LIMIT);
+        let end_index = from_index.saturating_add(limit).min(self.pools.len());
+        for pool_id in from_index..end_index {
+            let needs_upgrade = self.pool(pool_id).is_some_and(|pool| {
+                pool.vault_code_hash
+                    .is_some_and(|vault_code_hash| vault_code_hash != code_hash)
+            });
+            if needs_upgrade {
+                self.upgrade_vault(pool_id, code_hash);
+            }
+        }
+    }
+
+    #[private]
+    pub fn on_intents_vault_code_published(
+        &mut self,
+        code_hash: Base58CryptoHash,
+        publisher_id: AccountId,
+        storage_cost: NearToken,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) -> bool {
+        if call_result.is_err() {
+            log!("Failed to publish the intents vault code");
+            Promise::new(publisher_id).transfer(storage_cost);
+            return false;
+        }
+
+        self.intents_vault_code_hash = Some(code_hash);
+
+        Event::IntentsVaultCodePublished {
+            code_hash: &code_hash,
+        }
+        .emit();
+
+        true
+    }
+
+    #[private]
+    pub fn on_pool_vault_upgraded(
+        &mut self,
+        pool_id: u32,
+        code_hash: Base58CryptoHash,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) -> bool {
+        if call_result.is_err() {
+            log!("Failed to upgrade the vault of pool {}", pool_id);
+            return false;
+        }
+
+        let pool = self.pool_mut(pool_id).expect("Pool not found");
+        pool.vault_code_hash = Some(code_hash);
+        self.pools.flush();
+
+        Event::PoolVaultUpgraded {
+            pool_id: &pool_id,
+            code_hash: &code_hash,
+        }
+        .emit();
+
+        true
+    }
+}
+
+impl Contract {
+    pub(crate) fn published_intents_vault_code_hash(&self) -> Base58CryptoHash {
+        self.intents_vault_code_hash
+            .expect("Intents vault code not published")
+    }
+
+    fn upgrade_vault(&self, pool_id: u32, code_hash: Base58CryptoHash) -> Promise {
+        ext_intents_vault::ext(self.get_pool_account_id(pool_id))
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_UPGRADE_POOL_VAULT)
+            .upgrade(code_hash)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_UPGRADE_POOL_VAULT_CALLBACK)
+                    .with_unused_gas_weight(0)
+                    .on_pool_vault_upgraded(pool_id, code_hash),
+            )
+    }
+}
+
+/// Sha256 hash of the intents vault code bundled with the registry
+pub(crate) fn bundled_intents_vault_code_hash() -> Base58CryptoHash {
+    Base58CryptoHash::from(env::sha256_array(INTENTS_VAULT_WASM))
+}
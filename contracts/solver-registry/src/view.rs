@@ -1,5 +1,5 @@
 use crate::*;
-use near_sdk::AccountId;
+use near_sdk::{json_types::Base58CryptoHash, AccountId};
 
 #[near]
 impl Contract {
@@ -7,26 +7,54 @@ impl Contract {
         self.owner_id.clone()
     }
 
+    /// Get the version of the contract's storage layout
+    pub fn get_state_version(&self) -> u32 {
+        Self::read_state_version()
+    }
+
+    /// Get the account proposed as the new owner, pending its acceptance
+    pub fn get_pending_owner_id(&self) -> Option<AccountId> {
+        self.pending_owner_id.clone()
+    }
+
     /// Get all approved docker compose hashes
     pub fn get_approved_compose_hashes(&self) -> Vec<String> {
         self.approved_compose_hashes.iter().cloned().collect()
     }
 
+    /// Get the configuration bound to an approved docker compose hash
+    pub fn get_compose_hash_config(&self, compose_hash: String) -> Option<ComposeHashConfig> {
+        self.approved_compose_hashes
+            .contains(&compose_hash)
+            .then(|| self.get_compose_hash_config_or_default(&compose_hash))
+    }
+
     pub fn get_pool_len(&self) -> u32 {
         self.pools.len()
     }
 
     pub fn get_pool(&self, pool_id: u32) -> Option<PoolInfo> {
-        self.pools.get(pool_id).map(|p| PoolInfo {
+        self.pool(pool_id).map(|p| PoolInfo {
             token_ids: p.token_ids.clone(),
             amounts: p.amounts.iter().map(|a| (*a).into()).collect(),
             fee: p.fee,
             shares_total_supply: p.shares_total_supply.into(),
             worker_id: p.worker_id.clone(),
             last_ping_timestamp_ms: p.last_ping_timestamp_ms,
+            app_compose_policy: p.app_compose_policy.clone(),
+            vault_code_hash: p.vault_code_hash,
+            worker_ping_timeout_ms: p.worker_ping_timeout_ms,
+            effective_worker_ping_timeout_ms: p
+                .effective_worker_ping_timeout_ms(self.worker_ping_timeout_ms),
         })
     }
 
+    /// Get the sha256 hash of the intents vault code published as a global contract, used by
+    /// the accounts of new pools and by vault upgrades
+    pub fn get_intents_vault_code_hash(&self) -> Option<Base58CryptoHash> {
+        self.intents_vault_code_hash
+    }
+
     pub fn get_worker_len(&self) -> u32 {
         self.worker_by_account_id.len()
     }
@@ -43,7 +71,126 @@ impl Contract {
             .collect()
     }
 
+    /// Intents public keys left on the pools' accounts by failed key rotation rollbacks, with
+    /// their pool IDs
+    pub fn get_orphaned_worker_keys(&self) -> Vec<(PublicKey, u32)> {
+        self.orphaned_worker_keys
+            .iter()
+            .map(|(public_key, pool_id)| (public_key.clone(), *pool_id))
+            .collect()
+    }
+
+    /// Get the global worker ping timeout, used by the pools which don't override it
     pub fn get_worker_ping_timeout_ms(&self) -> TimestampMs {
         self.worker_ping_timeout_ms
     }
+
+    /// Get the pending registration challenge of a worker
+    pub fn get_registration_challenge(
+        &self,
+        account_id: AccountId,
+    ) -> Option<RegistrationChallenge> {
+        self.registration_challenges.get(&account_id).cloned()
+    }
+
+    pub fn get_registration_challenge_ttl_ms(&self) -> TimestampMs {
+        self.registration_challenge_ttl_ms
+    }
+
+    pub fn is_legacy_report_data_allowed(&self) -> bool {
+        self.allow_legacy_report_data
+    }
+
+    /// Get the curve types allowed for workers' intents public keys
+    pub fn get_allowed_key_types(&self) -> Vec<KeyType> {
+        self.allowed_key_types.clone()
+    }
+
+    /// Get the delay before scheduled sensitive admin actions can be executed
+    pub fn get_timelock_delay_ms(&self) -> TimestampMs {
+        self.timelock_delay_ms
+    }
+
+    pub fn get_scheduled_action(&self, action_id: ActionId) -> Option<ScheduledAction> {
+        self.scheduled_actions.get(&action_id).cloned()
+    }
+
+    /// Get the pending actions in the timelock queue with their IDs
+    pub fn get_scheduled_actions(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<(ActionId, ScheduledAction)> {
+        self.scheduled_actions
+            .iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|(action_id, scheduled_action)| (*action_id, scheduled_action.clone()))
+            .collect()
+    }
+
+    /// Get the guardians voting on compose hash changes, if the multisig mode is enabled
+    pub fn get_guardians(&self) -> Option<GuardianSet> {
+        self.guardian_set.clone()
+    }
+
+    /// Get the pending guardian votes on a compose hash
+    pub fn get_compose_hash_votes(&self, compose_hash: String) -> Option<ComposeHashVotes> {
+        self.compose_hash_votes.get(&compose_hash).cloned()
+    }
+
+    /// Get the pending guardian votes on an action
+    pub fn get_action_votes(&self, action: TimelockAction) -> Option<ActionVotes> {
+        self.action_votes
+            .get(&Self::action_votes_key(&action))
+            .cloned()
+    }
+
+    /// Get the paused features
+    pub fn get_paused_features(&self) -> Vec<PausableFeature> {
+        self.paused_features.clone()
+    }
+
+    /// Whether the account holds the role. The owner implicitly holds all roles.
+    pub fn has_role(&self, role: Role, account_id: AccountId) -> bool {
+        self.is_owner_or_has_role(role, &account_id)
+    }
+
+    /// Get the accounts granted the role, besides the owner
+    pub fn get_role_members(&self, role: Role) -> Vec<AccountId> {
+        self.role_members
+            .get(&role)
+            .map(|members| members.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the maximum age of the collateral of workers' attestations
+    pub fn get_max_collateral_age_ms(&self) -> TimestampMs {
+        self.max_collateral_age_ms
+    }
+
+    /// Get the validity of the cached collateral for the hex-encoded FMSPC
+    pub fn get_cached_collateral_validity(&self, fmspc: String) -> Option<CollateralValidity> {
+        let collateral = self.cached_collaterals.get(&fmspc.to_ascii_lowercase())?;
+        let dates = collateral.dates().ok()?;
+        Some(CollateralValidity {
+            issue_date_ms: dates.issue_date_s.saturating_mul(1000),
+            next_update_ms: dates.next_update_s.saturating_mul(1000),
+        })
+    }
+
+    /// Get the policy of accepted TCB statuses and tolerated security advisories
+    pub fn get_tcb_policy(&self) -> TcbPolicy {
+        self.tcb_policy.clone()
+    }
+
+    /// Get the name of the RTMR3 event which measures the solver's docker image digest
+    pub fn get_docker_image_hash_event(&self) -> Option<String> {
+        self.docker_image_hash_event.clone()
+    }
+
+    /// Get the global app compose policy for workers
+    pub fn get_app_compose_policy(&self) -> AppComposePolicy {
+        self.app_compose_policy.clone()
+    }
 }
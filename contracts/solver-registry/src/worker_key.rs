@@ -0,0 +1,177 @@
+use near_sdk::{log, near, Gas, PromiseError, PromiseOrValue};
+
+use crate::*;
+
+const GAS_ROLLBACK_KEY_REMOVED_CALLBACK: Gas = Gas::from_tgas(10);
+const GAS_ROTATED_OLD_KEY_REMOVED_CALLBACK: Gas = Gas::from_tgas(10) // 10 Tgas for the callback function itself
+    .saturating_add(GAS_REMOVE_WORKER_KEY)
+    .saturating_add(GAS_ROLLBACK_KEY_REMOVED_CALLBACK);
+const GAS_ROTATED_NEW_KEY_ADDED_CALLBACK: Gas = Gas::from_tgas(10) // 10 Tgas for the callback function itself
+    .saturating_add(GAS_REMOVE_WORKER_KEY)
+    .saturating_add(GAS_ROTATED_OLD_KEY_REMOVED_CALLBACK);
+
+#[near]
+impl Contract {
+    /// Rotate the intents public key of the pool's registered worker with a fresh TEE attestation
+    /// committing to the new key. The report data follows the same rules as `register_worker`.
+    ///
+    /// The new key is added to the intents contract before the old one is removed. If the old key
+    /// can't be removed, the new key is removed again and the worker keeps the old key. The new key
+    /// is removed as well if the worker was removed or replaced during the rotation. If the new key
+    /// can't be removed, it's recorded as orphaned until `revoke_all_worker_keys`.
+    #[payable]
+    pub fn rotate_worker_key(
+        &mut self,
+        new_public_key: PublicKey,
+        quote_hex: String,
+        collateral: Option<String>,
+        tcb_info: String,
+    ) -> Promise {
+        assert_one_yocto();
+        self.assert_not_paused(PausableFeature::RegisterWorker);
+        let worker_id = env::predecessor_account_id();
+        let worker = self
+            .get_worker(worker_id.clone())
+            .expect("Worker not found");
+
+        let pool = self.pool(worker.pool_id).expect("Pool not found");
+        require!(
+            pool.worker_id.as_ref() == Some(&worker_id),
+            "Only the registered worker can rotate its key"
+        );
+        require!(
+            worker.public_key != new_public_key,
+            "New public key must be different from the current one"
+        );
+
+        let new_worker = self.verify_worker_attestation(
+            worker.pool_id,
+            &quote_hex,
+            collateral.as_deref(),
+            worker.checksum.clone(),
+            &tcb_info,
+            new_public_key,
+        );
+
+        // Add the new public key to the intents vault first, so the worker is never left without a key
+        ext_intents_vault::ext(self.get_pool_account_id(worker.pool_id))
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_ADD_WORKER_KEY)
+            .with_unused_gas_weight(0)
+            .add_public_key(
+                self.intents_contract_id.clone(),
+                new_worker.public_key.clone(),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_ROTATED_NEW_KEY_ADDED_CALLBACK)
+                    .with_unused_gas_weight(0)
+                    .on_rotated_worker_key_added(worker_id, worker.public_key, new_worker),
+            )
+    }
+
+    #[private]
+    pub fn on_rotated_worker_key_added(
+        &mut self,
+        worker_id: AccountId,
+        old_public_key: PublicKey,
+        worker: Worker,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) -> Promise {
+        if call_result.is_err() {
+            env::panic_str("Failed to add new worker key");
+        }
+
+        // Remove the old public key from the intents vault
+        ext_intents_vault::ext(self.get_pool_account_id(worker.pool_id))
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_REMOVE_WORKER_KEY)
+            .with_unused_gas_weight(0)
+            .remove_public_key(self.intents_contract_id.clone(), old_public_key.clone())
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_ROTATED_OLD_KEY_REMOVED_CALLBACK)
+                    .with_unused_gas_weight(0)
+                    .on_rotated_old_worker_key_removed(worker_id, old_public_key, worker),
+            )
+    }
+
+    #[private]
+    pub fn on_rotated_old_worker_key_removed(
+        &mut self,
+        worker_id: AccountId,
+        old_public_key: PublicKey,
+        worker: Worker,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) -> PromiseOrValue<()> {
+        if call_result.is_err() {
+            // Roll back by removing the new public key, the worker keeps the old one
+            log!("Failed to remove old worker key, rolling back the key rotation");
+            return self.remove_rotated_worker_key(worker).into();
+        }
+
+        // The worker may have been removed or replaced while the keys were rotated
+        let is_same_worker = self
+            .pool(worker.pool_id)
+            .is_some_and(|pool| pool.worker_id.as_ref() == Some(&worker_id))
+            && self
+                .worker_by_account_id
+                .get(&worker_id)
+                .is_some_and(|current| {
+                    current.pool_id == worker.pool_id && current.public_key == old_public_key
+                });
+        if !is_same_worker {
+            log!("Worker changed during the key rotation, removing the new key");
+            return self.remove_rotated_worker_key(worker).into();
+        }
+
+        Event::WorkerKeyRotated {
+            worker_id: &worker_id,
+            pool_id: &worker.pool_id,
+            old_public_key: &old_public_key,
+            new_public_key: &worker.public_key,
+        }
+        .emit();
+
+        self.worker_by_account_id.insert(worker_id, worker);
+        PromiseOrValue::Value(())
+    }
+
+    #[private]
+    pub fn on_rotation_rollback_key_removed(
+        &mut self,
+        pool_id: u32,
+        public_key: PublicKey,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) {
+        if call_result.is_ok() {
+            return;
+        }
+
+        // The new key stays on the pool's account without a worker
+        Event::WorkerKeyOrphaned {
+            pool_id: &pool_id,
+            public_key: &public_key,
+        }
+        .emit();
+        self.orphaned_worker_keys.insert(public_key, pool_id);
+    }
+}
+
+impl Contract {
+    /// Remove the new key of a key rotation which didn't complete. The key is recorded as
+    /// orphaned if it can't be removed.
+    fn remove_rotated_worker_key(&self, worker: Worker) -> Promise {
+        ext_intents_vault::ext(self.get_pool_account_id(worker.pool_id))
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_REMOVE_WORKER_KEY)
+            .with_unused_gas_weight(0)
+            .remove_public_key(self.intents_contract_id.clone(), worker.public_key.clone())
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_ROLLBACK_KEY_REMOVED_CALLBACK)
+                    .with_unused_gas_weight(0)
+                    .on_rotation_rollback_key_removed(worker.pool_id, worker.public_key),
+            )
+    }
+}
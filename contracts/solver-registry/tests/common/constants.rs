@@ -1,4 +1,25 @@
 pub const COMPOSE_HASH: &str = "f68cf65a75ad980289144ef3c096a007fc4583ea6d1f90589757f229dbc6cdab";
+pub const PRE_LAUNCH_SCRIPT_HASH: &str =
+    "1189e1d212cd248a306da5d4fae367a3d942f2ac2659dd2c4ff4eaad13922c44";
+pub const DOCKER_IMAGE_HASH: &str =
+    "660825a1ef7c98ee64bacf5b00ee4d526364a4a8417e1b8898fa9f6bcebef6c7";
+// RTMR3 event measured once by the samples, standing in for the docker image hash event
+pub const MEASURED_IMAGE_HASH_EVENT: &str = "os-image-hash";
+pub const MEASURED_IMAGE_HASH: &str =
+    "2d24d302cc8686d6a0ece71a6afef55c506bc8591e3bcc1ec3eb5323d77582c4";
+// Time of the attestation clock at which the samples are verified, 2025-09-03T00:00:00Z, between
+// the issue dates and the next updates of their collaterals
+pub const SAMPLE_ATTESTATION_TIMESTAMP_MS: u64 = 1_756_857_600_000;
+// Next update of the QE identity of the sample collaterals, which expires before their TCB info
+pub const SAMPLE_COLLATERAL_NEXT_UPDATE_MS: u64 = 1_759_389_003_000;
+pub const ALLOWED_ENVS: [&str; 6] = [
+    "NEAR_NETWORK_ID",
+    "SOLVER_REGISTRY_CONTRACT",
+    "SOLVER_POOL_ID",
+    "AMM_TOKEN1_ID",
+    "AMM_TOKEN2_ID",
+    "INTENTS_CONTRACT",
+];
 
 // Worker Info: Alice
 pub const SECRET_KEY_ALICE: &str = "ed25519:3uHrtHQ6422oAj7WhvDgf9KdewGZLvCLbY6AyDdfkctRkUgyai1yMFn7TGnY2a4zQ8o2a1xQpaPPuaTcjRNaxTqP";
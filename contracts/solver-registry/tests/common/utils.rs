@@ -5,10 +5,16 @@ use near_contract_standards::fungible_token::{metadata::FungibleTokenMetadata, B
 use near_gas::NearGas;
 use near_sdk::{AccountId, NearToken, PublicKey};
 use near_workspaces::{
-    network::Sandbox, result::ExecutionFinalResult, types::SecretKey, Account, Contract, Worker,
+    network::Sandbox,
+    result::ExecutionFinalResult,
+    types::{AccessKey, SecretKey},
+    Account, Contract, Worker,
 };
 use serde_json::json;
-use solver_registry::{pool::PoolInfo, types::TimestampMs};
+use solver_registry::{
+    pool::PoolInfo,
+    types::{CollateralValidity, RegistrationChallenge, TimestampMs},
+};
 
 use super::constants::*;
 
@@ -18,6 +24,9 @@ pub const SOLVER_REGISTRY_CONTRACT_WASM: &str =
     "../../target/near/solver_registry/solver_registry.wasm";
 pub const MOCK_INTENTS_CONTRACT_WASM: &str = "../../target/near/mock_intents/mock_intents.wasm";
 pub const MOCK_FT_CONTRACT_WASM: &str = "../../target/near/mock_ft/mock_ft.wasm";
+/// Intents vault built with the `test-upgrade` feature, which logs its migration
+pub const INTENTS_VAULT_TEST_UPGRADE_WASM: &str =
+    "../../target/near/intents_vault/intents_vault_test_upgrade.wasm";
 
 pub async fn create_account(
     sandbox: &Worker<Sandbox>,
@@ -49,6 +58,25 @@ pub async fn create_account_with_secret_key(
         .result)
 }
 
+// Helper function to add the secret key as an access key of the account, and sign with it
+pub async fn add_access_key(
+    sandbox: &Worker<Sandbox>,
+    account: &Account,
+    secret_key: &SecretKey,
+) -> Result<Account, Box<dyn std::error::Error>> {
+    account
+        .batch(account.id())
+        .add_key(secret_key.public_key(), AccessKey::full_access())
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(Account::from_secret_key(
+        account.id().clone(),
+        secret_key.clone(),
+        sandbox,
+    ))
+}
+
 pub async fn create_ft(
     sandbox: &Worker<Sandbox>,
     name: &str,
@@ -175,9 +203,120 @@ pub async fn deploy_solver_registry(
         .await?;
     println!("\nResult init: {:?}", result);
 
+    // The accounts of new pools use the intents vault code published as a global contract
+    let result = publish_intents_vault_code(owner, &solver_registry_contract).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // The samples are verified at a fixed time within their collateral's validity, independently
+    // of the sandbox's wall-clock time
+    let result = set_attestation_timestamp_ms(
+        owner,
+        &solver_registry_contract,
+        SAMPLE_ATTESTATION_TIMESTAMP_MS,
+    )
+    .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
     Ok(solver_registry_contract)
 }
 
+// Helper function to set the attestation clock of a registry built with the `test-clock` feature
+pub async fn set_attestation_timestamp_ms(
+    owner: &Account,
+    solver_registry: &Contract,
+    timestamp_ms: TimestampMs,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "set_attestation_timestamp_ms")
+        .args_json(json!({ "timestamp_ms": timestamp_ms }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+// Helper function to publish the intents vault code as a global contract
+pub async fn publish_intents_vault_code(
+    owner: &Account,
+    solver_registry: &Contract,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "publish_intents_vault_code")
+        .deposit(NearToken::from_near(15))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+// Helper function to allow the report data V1 of the attestation samples, which are not bound to a
+// registration challenge
+pub async fn allow_legacy_report_data(
+    owner: &Account,
+    solver_registry: &Contract,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = set_allow_legacy_report_data(owner, solver_registry, true).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    Ok(())
+}
+
+// Helper function to allow or disallow worker registration with the report data V1
+pub async fn set_allow_legacy_report_data(
+    owner: &Account,
+    solver_registry: &Contract,
+    allow_legacy_report_data: bool,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "set_allow_legacy_report_data")
+        .args_json(json!({
+            "allow_legacy_report_data": allow_legacy_report_data
+        }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+// Helper function to request a registration challenge as a worker
+pub async fn request_registration_challenge(
+    worker: &Account,
+    solver_registry: &Contract,
+    pool_id: u32,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = worker
+        .call(solver_registry.id(), "request_registration_challenge")
+        .args_json(json!({
+            "pool_id": pool_id
+        }))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+// Helper function to get the pending registration challenge of a worker
+pub async fn get_registration_challenge(
+    solver_registry: &Contract,
+    account_id: &Account,
+) -> Result<Option<RegistrationChallenge>, Box<dyn std::error::Error>> {
+    let result = solver_registry
+        .view("get_registration_challenge")
+        .args_json(json!({"account_id" : account_id.id()}))
+        .await?;
+    let challenge: Option<RegistrationChallenge> = serde_json::from_slice(&result.result).unwrap();
+    Ok(challenge)
+}
+
 pub async fn deposit_into_pool(
     solver_registry: &Contract,
     user: &Account,
@@ -243,7 +382,7 @@ pub async fn create_test_tokens(
 pub async fn create_test_accounts(
     sandbox: &Worker<Sandbox>,
 ) -> Result<(Account, Account, Account), Box<dyn std::error::Error>> {
-    let owner = create_account(sandbox, "owner", 10).await?;
+    let owner = create_account(sandbox, "owner", 100).await?;
     let alice = create_account_with_secret_key(
         sandbox,
         "alice",
@@ -340,7 +479,7 @@ pub async fn create_liquidity_pool(
             "token_ids": [wnear.id(), usdc.id()],
             "fee": 300
         }))
-        .deposit(NearToken::from_yoctonear(1_500_000_000_000_000_000_000_000)) // 1.5 NEAR
+        .deposit(NearToken::from_millinear(100)) // 0.1 NEAR
         .gas(NearGas::from_tgas(300))
         .transact()
         .await?;
@@ -372,7 +511,110 @@ pub async fn approve_compose_hash(
     Ok(())
 }
 
-// Helper function to register a worker
+// Helper function to approve the pre-launch script of the samples for the compose hash
+pub async fn approve_sample_pre_launch_script(
+    owner: &Account,
+    solver_registry: &Contract,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = approve_pre_launch_script(owner, solver_registry, PRE_LAUNCH_SCRIPT_HASH).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    Ok(())
+}
+
+// Helper function to approve pre-launch script for the compose hash
+pub async fn approve_pre_launch_script(
+    owner: &Account,
+    solver_registry: &Contract,
+    pre_launch_script_hash: &str,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "approve_pre_launch_script")
+        .args_json(json!({
+            "compose_hash": COMPOSE_HASH,
+            "pre_launch_script_hash": pre_launch_script_hash
+        }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+// Helper function to approve or remove a docker image hash for the compose hash
+pub async fn update_docker_image_hash(
+    owner: &Account,
+    solver_registry: &Contract,
+    docker_image_hash: &str,
+    approve: bool,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let method = if approve {
+        "approve_docker_image_hash"
+    } else {
+        "remove_docker_image_hash"
+    };
+    let result = owner
+        .call(solver_registry.id(), method)
+        .args_json(json!({
+            "compose_hash": COMPOSE_HASH,
+            "docker_image_hash": docker_image_hash
+        }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+// Helper function to set the RTMR3 event which measures the docker image hash
+pub async fn set_docker_image_hash_event(
+    owner: &Account,
+    solver_registry: &Contract,
+    docker_image_hash_event: Option<&str>,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "set_docker_image_hash_event")
+        .args_json(json!({
+            "docker_image_hash_event": docker_image_hash_event
+        }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+// Helper function to set the allowed environment variables for the compose hash
+pub async fn set_compose_hash_allowed_envs(
+    owner: &Account,
+    solver_registry: &Contract,
+    allowed_envs: Option<Vec<&str>>,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "set_compose_hash_allowed_envs")
+        .args_json(json!({
+            "compose_hash": COMPOSE_HASH,
+            "allowed_envs": allowed_envs
+        }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+// Helper function to set the curve types allowed for workers' intents public keys
+pub async fn set_allowed_key_types(
+    owner: &Account,
+    solver_registry: &Contract,
+    allowed_key_types: &[&str],
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "set_allowed_key_types")
+        .args_json(json!({
+            "allowed_key_types": allowed_key_types
+        }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+// Helper function to register a worker with its own public key as the intents public key
 pub async fn register_worker(
     worker: &Account,
     solver_registry: &Contract,
@@ -381,6 +623,31 @@ pub async fn register_worker(
     collateral: &str,
     checksum: &str,
     tcb_info: &str,
+) -> Result<near_workspaces::result::ExecutionFinalResult, Box<dyn std::error::Error>> {
+    register_worker_with_intents_key(
+        worker,
+        solver_registry,
+        pool_id,
+        quote_hex,
+        collateral,
+        checksum,
+        tcb_info,
+        &worker.secret_key().public_key().to_string(),
+    )
+    .await
+}
+
+// Helper function to register a worker with the given intents public key
+#[allow(clippy::too_many_arguments)]
+pub async fn register_worker_with_intents_key(
+    worker: &Account,
+    solver_registry: &Contract,
+    pool_id: u32,
+    quote_hex: &str,
+    collateral: &str,
+    checksum: &str,
+    tcb_info: &str,
+    intents_public_key: &str,
 ) -> Result<near_workspaces::result::ExecutionFinalResult, Box<dyn std::error::Error>> {
     let result = worker
         .call(solver_registry.id(), "register_worker")
@@ -389,6 +656,126 @@ pub async fn register_worker(
             "quote_hex": quote_hex.to_string(),
             "collateral": collateral.to_string(),
             "checksum": checksum.to_string(),
+            "tcb_info": tcb_info.to_string(),
+            "intents_public_key": intents_public_key
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+
+    print_logs(&result);
+    Ok(result)
+}
+
+// Helper function to register a worker against the cached collateral for its quote's FMSPC
+pub async fn register_worker_with_cached_collateral(
+    worker: &Account,
+    solver_registry: &Contract,
+    pool_id: u32,
+    quote_hex: &str,
+    checksum: &str,
+    tcb_info: &str,
+) -> Result<near_workspaces::result::ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = worker
+        .call(solver_registry.id(), "register_worker")
+        .args_json(json!({
+            "pool_id": pool_id,
+            "quote_hex": quote_hex.to_string(),
+            "checksum": checksum.to_string(),
+            "tcb_info": tcb_info.to_string(),
+            "intents_public_key": worker.secret_key().public_key().to_string()
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+
+    print_logs(&result);
+    Ok(result)
+}
+
+pub async fn set_collateral(
+    owner: &Account,
+    solver_registry: &Contract,
+    collateral: &str,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "set_collateral")
+        .args_json(json!({
+            "collateral": collateral.to_string()
+        }))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+pub async fn remove_collateral(
+    owner: &Account,
+    solver_registry: &Contract,
+    fmspc: &str,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "remove_collateral")
+        .args_json(json!({
+            "fmspc": fmspc
+        }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+pub async fn get_cached_collateral_validity(
+    solver_registry: &Contract,
+    fmspc: &str,
+) -> Result<Option<CollateralValidity>, Box<dyn std::error::Error>> {
+    let result = solver_registry
+        .view("get_cached_collateral_validity")
+        .args_json(json!({"fmspc": fmspc}))
+        .await?;
+    Ok(serde_json::from_slice(&result.result)?)
+}
+
+// Helper function to rotate the intents public key of a worker
+pub async fn rotate_worker_key(
+    worker: &Account,
+    solver_registry: &Contract,
+    new_public_key: &str,
+    quote_hex: &str,
+    collateral: &str,
+    tcb_info: &str,
+) -> Result<near_workspaces::result::ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = worker
+        .call(solver_registry.id(), "rotate_worker_key")
+        .args_json(json!({
+            "new_public_key": new_public_key,
+            "quote_hex": quote_hex.to_string(),
+            "collateral": collateral.to_string(),
+            "tcb_info": tcb_info.to_string()
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+
+    print_logs(&result);
+    Ok(result)
+}
+
+// Helper function to refresh the attestation of a registered worker
+pub async fn refresh_worker_attestation(
+    worker: &Account,
+    solver_registry: &Contract,
+    quote_hex: &str,
+    collateral: &str,
+    tcb_info: &str,
+) -> Result<near_workspaces::result::ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = worker
+        .call(solver_registry.id(), "refresh_worker_attestation")
+        .args_json(json!({
+            "quote_hex": quote_hex.to_string(),
+            "collateral": collateral.to_string(),
             "tcb_info": tcb_info.to_string()
         }))
         .deposit(NearToken::from_yoctonear(1))
@@ -400,6 +787,21 @@ pub async fn register_worker(
     Ok(result)
 }
 
+pub async fn set_max_collateral_age_ms(
+    owner: &Account,
+    solver_registry: &Contract,
+    max_collateral_age_ms: TimestampMs,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "set_max_collateral_age_ms")
+        .args_json(json!({
+            "max_collateral_age_ms": max_collateral_age_ms
+        }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
 // Helper function to register Alice as a worker
 pub async fn register_worker_alice(
     alice: &Account,
@@ -436,6 +838,38 @@ pub async fn register_worker_bob(
     .await
 }
 
+// Helper function to set the global worker ping timeout
+pub async fn set_worker_ping_timeout_ms(
+    account: &Account,
+    solver_registry: &Contract,
+    worker_ping_timeout_ms: TimestampMs,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = account
+        .call(solver_registry.id(), "set_worker_ping_timeout_ms")
+        .args_json(json!({ "worker_ping_timeout_ms": worker_ping_timeout_ms }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+// Helper function to override the worker ping timeout of a pool
+pub async fn set_pool_worker_ping_timeout_ms(
+    account: &Account,
+    solver_registry: &Contract,
+    pool_id: u32,
+    worker_ping_timeout_ms: Option<TimestampMs>,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = account
+        .call(solver_registry.id(), "set_pool_worker_ping_timeout_ms")
+        .args_json(json!({
+            "pool_id": pool_id,
+            "worker_ping_timeout_ms": worker_ping_timeout_ms
+        }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
 // Helper function to wait for worker timeout
 pub async fn wait_for_worker_timeout(timeout_seconds: u64) {
     println!(
@@ -459,6 +893,63 @@ pub async fn get_worker_info(
     Ok(worker_info)
 }
 
+pub async fn grant_role(
+    owner: &Account,
+    solver_registry: &Contract,
+    role: &str,
+    account_id: &AccountId,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "grant_role")
+        .args_json(json!({
+            "role": role,
+            "account_id": account_id
+        }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+pub async fn revoke_role(
+    owner: &Account,
+    solver_registry: &Contract,
+    role: &str,
+    account_id: &AccountId,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "revoke_role")
+        .args_json(json!({
+            "role": role,
+            "account_id": account_id
+        }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+pub async fn has_role(
+    solver_registry: &Contract,
+    role: &str,
+    account_id: &AccountId,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let result = solver_registry
+        .view("has_role")
+        .args_json(json!({"role": role, "account_id": account_id}))
+        .await?;
+    Ok(serde_json::from_slice(&result.result)?)
+}
+
+pub async fn get_role_members(
+    solver_registry: &Contract,
+    role: &str,
+) -> Result<Vec<AccountId>, Box<dyn std::error::Error>> {
+    let result = solver_registry
+        .view("get_role_members")
+        .args_json(json!({"role": role}))
+        .await?;
+    Ok(serde_json::from_slice(&result.result)?)
+}
+
 // Helper function to get pool info
 pub async fn get_pool_info(
     solver_registry: &Contract,
@@ -526,6 +1017,62 @@ pub async fn get_pool_public_keys(
     Ok(public_keys)
 }
 
+// Helper function to make the removal of a public key fail in the mock-intents contract
+pub async fn set_public_key_removal_failing(
+    mock_intents: &Contract,
+    public_key: &PublicKey,
+    failing: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    mock_intents
+        .call("set_public_key_removal_failing")
+        .args_json(json!({"public_key": public_key, "failing": failing}))
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+// Helper function to get the orphaned worker keys with their pool IDs
+pub async fn get_orphaned_worker_keys(
+    solver_registry: &Contract,
+) -> Result<Vec<(PublicKey, u32)>, Box<dyn std::error::Error>> {
+    let result = solver_registry
+        .view("get_orphaned_worker_keys")
+        .args_json(json!({}))
+        .await?;
+    Ok(serde_json::from_slice(&result.result)?)
+}
+
+// Helper function to revoke the keys of all workers
+pub async fn revoke_all_worker_keys(
+    pauser: &Account,
+    solver_registry: &Contract,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = pauser
+        .call(solver_registry.id(), "revoke_all_worker_keys")
+        .args_json(json!({}))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    print_logs(&result);
+    Ok(result)
+}
+
+// Helper function to revoke the worker keys orphaned by failed key rotation rollbacks
+pub async fn revoke_orphaned_worker_keys(
+    pauser: &Account,
+    solver_registry: &Contract,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = pauser
+        .call(solver_registry.id(), "revoke_orphaned_worker_keys")
+        .args_json(json!({}))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    print_logs(&result);
+    Ok(result)
+}
+
 // Helper function to get pool account ID from pool ID
 pub fn get_pool_account_id(solver_registry: &Contract, pool_id: u32) -> AccountId {
     format!("pool-{}.{}", pool_id, solver_registry.id())
@@ -0,0 +1,160 @@
+mod common;
+
+use common::constants::*;
+use common::utils::*;
+
+/// FMSPC of the platform of the sample quotes
+const SAMPLE_FMSPC: &str = "20A06F000000";
+
+#[tokio::test]
+async fn test_register_worker_with_cached_collateral() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for worker registration with cached collateral...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Approve compose hash and the pre-launch script of the samples
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+
+    // Registration without collateral fails if none is cached
+    let result = register_worker_with_cached_collateral(
+        &alice,
+        &solver_registry,
+        0,
+        QUOTE_HEX_ALICE,
+        CHECKSUM_ALICE,
+        TCB_INFO_ALICE,
+    )
+    .await?;
+    assert!(
+        !result.is_success(),
+        "Registration should fail without cached collateral"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Collateral not cached"));
+
+    // Only the owner can cache collateral without a quote
+    let result = set_collateral(&alice, &solver_registry, QUOTE_COLLATERAL_ALICE).await?;
+    assert!(!result.is_success(), "Non-owner can't set collateral");
+
+    let result = set_collateral(&owner, &solver_registry, QUOTE_COLLATERAL_ALICE).await?;
+    assert!(
+        result.is_success(),
+        "Owner can set collateral: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let validity = get_cached_collateral_validity(&solver_registry, SAMPLE_FMSPC)
+        .await?
+        .expect("Collateral should be cached");
+    assert_eq!(validity.issue_date_ms, 1_756_797_003_000);
+    assert_eq!(validity.next_update_ms, 1_759_389_003_000);
+
+    // The cached collateral can only be replaced by a newer one
+    let result = set_collateral(&owner, &solver_registry, QUOTE_COLLATERAL_ALICE).await?;
+    assert!(
+        !result.is_success(),
+        "Collateral should not be replaced by the same one"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Collateral is not newer than the cached one"));
+
+    // Register Alice against the cached collateral
+    let result = register_worker_with_cached_collateral(
+        &alice,
+        &solver_registry,
+        0,
+        QUOTE_HEX_ALICE,
+        CHECKSUM_ALICE,
+        TCB_INFO_ALICE,
+    )
+    .await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(get_worker_info(&solver_registry, &alice).await?.is_some());
+
+    // Remove the cached collateral
+    let result = remove_collateral(&alice, &solver_registry, SAMPLE_FMSPC).await?;
+    assert!(!result.is_success(), "Non-owner can't remove collateral");
+    let result = remove_collateral(&owner, &solver_registry, SAMPLE_FMSPC).await?;
+    assert!(result.is_success(), "Owner can remove collateral");
+    assert!(
+        get_cached_collateral_validity(&solver_registry, SAMPLE_FMSPC)
+            .await?
+            .is_none()
+    );
+
+    println!("Test passed: Workers register against the cached collateral");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_register_worker_with_stale_cached_collateral(
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for worker registration with stale cached collateral...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Approve compose hash and the pre-launch script of the samples
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+
+    // Cache a collateral whose QE identity expired before the attestation time
+    let stale_collateral =
+        QUOTE_COLLATERAL_ALICE.replace("2025-10-02T07:10:03Z", "2025-09-02T08:00:00Z");
+    let result = set_collateral(&owner, &solver_registry, &stale_collateral).await?;
+    assert!(
+        result.is_success(),
+        "Owner can set collateral: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // The stale cached collateral fails the registration without the worker's collateral
+    let result = register_worker_with_cached_collateral(
+        &alice,
+        &solver_registry,
+        0,
+        QUOTE_HEX_ALICE,
+        CHECKSUM_ALICE,
+        TCB_INFO_ALICE,
+    )
+    .await?;
+    assert!(
+        !result.is_success(),
+        "Registration should fail with stale cached collateral"
+    );
+
+    // The worker's collateral is used instead of the stale cached one
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let worker = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should be registered as a worker");
+    assert_eq!(
+        worker.attestation_valid_until_ms,
+        SAMPLE_COLLATERAL_NEXT_UPDATE_MS
+    );
+
+    println!("Test passed: Workers register against their collateral if the cached one is stale");
+    Ok(())
+}
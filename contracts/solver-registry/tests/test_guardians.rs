@@ -0,0 +1,259 @@
+mod common;
+
+use common::constants::*;
+use common::utils::*;
+use near_workspaces::{result::ExecutionFinalResult, Account, Contract};
+use serde_json::json;
+
+async fn vote_compose_hash(
+    guardian: &Account,
+    solver_registry: &Contract,
+    approve: bool,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = guardian
+        .call(solver_registry.id(), "vote_compose_hash")
+        .args_json(json!({ "compose_hash": COMPOSE_HASH, "approve": approve }))
+        .transact()
+        .await?;
+    print_logs(&result);
+    Ok(result)
+}
+
+async fn is_compose_hash_approved(
+    solver_registry: &Contract,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let approved_compose_hashes: Vec<String> = solver_registry
+        .view("get_approved_compose_hashes")
+        .await?
+        .json()?;
+    Ok(approved_compose_hashes.contains(&COMPOSE_HASH.to_string()))
+}
+
+#[tokio::test]
+async fn test_compose_hash_guardian_votes() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for compose hash guardian votes...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (_wnear, _usdc, owner, alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // The threshold can't exceed the number of guardians
+    let result = owner
+        .call(solver_registry.id(), "set_guardians")
+        .args_json(json!({
+            "guardian_set": { "guardians": [alice.id(), bob.id()], "threshold": 3 }
+        }))
+        .transact()
+        .await?;
+    assert!(!result.is_success(), "Threshold must not exceed guardians");
+
+    // Enable the multisig mode with 2-of-2 guardians
+    let result = owner
+        .call(solver_registry.id(), "set_guardians")
+        .args_json(json!({
+            "guardian_set": { "guardians": [alice.id(), bob.id()], "threshold": 2 }
+        }))
+        .transact()
+        .await?;
+    assert!(result.is_success(), "Owner can set guardians");
+
+    // The owner can't approve compose hashes on its own anymore
+    let result = owner
+        .call(solver_registry.id(), "approve_compose_hash")
+        .args_json(json!({ "compose_hash": COMPOSE_HASH }))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Owner approval should fail in multisig mode"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Compose hash changes require guardian votes"));
+
+    // Only guardians can vote
+    let result = vote_compose_hash(&owner, &solver_registry, true).await?;
+    assert!(!result.is_success(), "Owner is not a guardian");
+
+    // The compose hash is approved once the threshold is met
+    let result = vote_compose_hash(&alice, &solver_registry, true).await?;
+    assert!(result.is_success(), "Alice can vote");
+    assert!(!result.json::<bool>()?);
+    assert!(!is_compose_hash_approved(&solver_registry).await?);
+
+    let result = vote_compose_hash(&alice, &solver_registry, true).await?;
+    assert!(!result.is_success(), "Alice can't vote twice");
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Already voted"));
+
+    let result = vote_compose_hash(&bob, &solver_registry, true).await?;
+    assert!(result.is_success(), "Bob can vote");
+    assert!(result.json::<bool>()?);
+    assert!(is_compose_hash_approved(&solver_registry).await?);
+
+    // The compose hash is removed by guardian votes as well
+    let result = owner
+        .call(solver_registry.id(), "remove_compose_hash")
+        .args_json(json!({ "compose_hash": COMPOSE_HASH }))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Owner removal should fail in multisig mode"
+    );
+
+    let result = vote_compose_hash(&bob, &solver_registry, false).await?;
+    assert!(!result.json::<bool>()?);
+    assert!(is_compose_hash_approved(&solver_registry).await?);
+    let result = vote_compose_hash(&alice, &solver_registry, false).await?;
+    assert!(result.json::<bool>()?);
+    assert!(!is_compose_hash_approved(&solver_registry).await?);
+
+    println!("Test passed: Compose hash changes require guardian votes");
+    Ok(())
+}
+
+async fn vote_action(
+    guardian: &Account,
+    solver_registry: &Contract,
+    action: serde_json::Value,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = guardian
+        .call(solver_registry.id(), "vote_action")
+        .args_json(json!({ "action": action }))
+        .transact()
+        .await?;
+    print_logs(&result);
+    Ok(result)
+}
+
+#[tokio::test]
+async fn test_guardian_voted_actions() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for guardian voted actions...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (_wnear, _usdc, owner, alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Approve the compose hash, then enable the multisig mode with 2-of-2 guardians
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let result = owner
+        .call(solver_registry.id(), "set_guardians")
+        .args_json(json!({
+            "guardian_set": { "guardians": [alice.id(), bob.id()], "threshold": 2 }
+        }))
+        .transact()
+        .await?;
+    assert!(result.is_success(), "Owner can set guardians");
+
+    // The owner can't change the compose hash configuration on its own anymore
+    let result =
+        approve_pre_launch_script(&owner, &solver_registry, PRE_LAUNCH_SCRIPT_HASH).await?;
+    assert!(
+        !result.is_success(),
+        "Owner pre-launch script approval should fail in multisig mode"
+    );
+    let result =
+        update_docker_image_hash(&owner, &solver_registry, DOCKER_IMAGE_HASH, true).await?;
+    assert!(
+        !result.is_success(),
+        "Owner docker image approval should fail in multisig mode"
+    );
+    let result =
+        set_compose_hash_allowed_envs(&owner, &solver_registry, Some(ALLOWED_ENVS.to_vec()))
+            .await?;
+    assert!(
+        !result.is_success(),
+        "Owner allowed envs update should fail in multisig mode"
+    );
+
+    // The guardians approve the pre-launch script
+    let action = json!({
+        "ApprovePreLaunchScript": {
+            "compose_hash": COMPOSE_HASH,
+            "pre_launch_script_hash": PRE_LAUNCH_SCRIPT_HASH
+        }
+    });
+    let result = vote_action(&alice, &solver_registry, action.clone()).await?;
+    assert!(result.is_success(), "Alice can vote");
+    assert!(!result.json::<bool>()?);
+    let result = vote_action(&bob, &solver_registry, action).await?;
+    assert!(result.is_success(), "Bob can vote");
+    assert!(result.json::<bool>()?);
+    let config: serde_json::Value = solver_registry
+        .view("get_compose_hash_config")
+        .args_json(json!({ "compose_hash": COMPOSE_HASH }))
+        .await?
+        .json()?;
+    assert_eq!(
+        config["pre_launch_script_hashes"],
+        json!([PRE_LAUNCH_SCRIPT_HASH])
+    );
+
+    // Only the guardians can remove the pre-launch script
+    let result = owner
+        .call(solver_registry.id(), "remove_pre_launch_script")
+        .args_json(json!({
+            "compose_hash": COMPOSE_HASH,
+            "pre_launch_script_hash": PRE_LAUNCH_SCRIPT_HASH
+        }))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Owner pre-launch script removal should fail in multisig mode"
+    );
+    let action = json!({
+        "RemovePreLaunchScript": {
+            "compose_hash": COMPOSE_HASH,
+            "pre_launch_script_hash": PRE_LAUNCH_SCRIPT_HASH
+        }
+    });
+    let result = vote_action(&alice, &solver_registry, action.clone()).await?;
+    assert!(!result.json::<bool>()?);
+    let result = vote_action(&bob, &solver_registry, action).await?;
+    assert!(result.json::<bool>()?);
+    let config: serde_json::Value = solver_registry
+        .view("get_compose_hash_config")
+        .args_json(json!({ "compose_hash": COMPOSE_HASH }))
+        .await?
+        .json()?;
+    assert_eq!(config["pre_launch_script_hashes"], json!([]));
+
+    // Other actions can't be voted
+    let result = vote_action(
+        &alice,
+        &solver_registry,
+        json!({ "SetAllowLegacyReportData": { "allow_legacy_report_data": true } }),
+    )
+    .await?;
+    assert!(!result.is_success(), "Only some actions can be voted");
+
+    // The owner can't disable the multisig mode without a timelock delay
+    let result = owner
+        .call(solver_registry.id(), "set_guardians")
+        .args_json(json!({ "guardian_set": null }))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Owner can't disable the multisig mode without a timelock delay"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error)
+        .contains("Guardian changes require guardian votes or a timelock delay"));
+
+    // The guardians can disable it
+    let action = json!({ "SetGuardians": { "guardian_set": null } });
+    let result = vote_action(&alice, &solver_registry, action.clone()).await?;
+    assert!(!result.json::<bool>()?);
+    let result = vote_action(&bob, &solver_registry, action).await?;
+    assert!(result.json::<bool>()?);
+    let guardians: Option<serde_json::Value> =
+        solver_registry.view("get_guardians").await?.json()?;
+    assert!(guardians.is_none());
+
+    println!("Test passed: Guardians vote on the compose hash configuration and the guardians");
+    Ok(())
+}
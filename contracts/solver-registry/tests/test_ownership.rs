@@ -0,0 +1,126 @@
+mod common;
+
+use common::utils::*;
+use near_sdk::AccountId;
+use near_workspaces::{result::ExecutionFinalResult, Account, Contract};
+use serde_json::json;
+
+async fn call_owner_method(
+    account: &Account,
+    solver_registry: &Contract,
+    method: &str,
+    args: serde_json::Value,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    Ok(account
+        .call(solver_registry.id(), method)
+        .args_json(args)
+        .transact()
+        .await?)
+}
+
+async fn get_owners(
+    solver_registry: &Contract,
+) -> Result<(AccountId, Option<AccountId>), Box<dyn std::error::Error>> {
+    let owner_id = solver_registry.view("get_owner_id").await?.json()?;
+    let pending_owner_id = solver_registry.view("get_pending_owner_id").await?.json()?;
+    Ok((owner_id, pending_owner_id))
+}
+
+#[tokio::test]
+async fn test_two_step_ownership_transfer() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for two-step ownership transfer...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (_wnear, _usdc, owner, alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Only the owner can propose a new owner
+    let result = call_owner_method(
+        &alice,
+        &solver_registry,
+        "propose_owner",
+        json!({ "new_owner_id": alice.id() }),
+    )
+    .await?;
+    assert!(!result.is_success(), "Non-owner can't propose a new owner");
+
+    // The ownership doesn't change until the proposal is accepted
+    let result = call_owner_method(
+        &owner,
+        &solver_registry,
+        "propose_owner",
+        json!({ "new_owner_id": alice.id() }),
+    )
+    .await?;
+    assert!(result.is_success(), "Owner can propose a new owner");
+    assert!(result
+        .logs()
+        .iter()
+        .any(|log| log.contains("ownership_transfer_proposed")));
+    assert_eq!(
+        get_owners(&solver_registry).await?,
+        (owner.id().clone(), Some(alice.id().clone()))
+    );
+
+    // The proposal can be cancelled
+    let result = call_owner_method(
+        &owner,
+        &solver_registry,
+        "cancel_ownership_proposal",
+        json!({}),
+    )
+    .await?;
+    assert!(result.is_success(), "Owner can cancel the proposal");
+    let result = call_owner_method(&alice, &solver_registry, "accept_ownership", json!({})).await?;
+    assert!(!result.is_success(), "Cancelled proposal can't be accepted");
+    assert_eq!(
+        get_owners(&solver_registry).await?,
+        (owner.id().clone(), None)
+    );
+
+    // Only the pending owner can accept the ownership
+    let result = call_owner_method(
+        &owner,
+        &solver_registry,
+        "propose_owner",
+        json!({ "new_owner_id": alice.id() }),
+    )
+    .await?;
+    assert!(result.is_success(), "Owner can propose a new owner");
+    let result = call_owner_method(&bob, &solver_registry, "accept_ownership", json!({})).await?;
+    assert!(
+        !result.is_success(),
+        "Only the pending owner can accept the ownership"
+    );
+
+    let result = call_owner_method(&alice, &solver_registry, "accept_ownership", json!({})).await?;
+    assert!(
+        result.is_success(),
+        "Pending owner can accept the ownership"
+    );
+    assert!(result
+        .logs()
+        .iter()
+        .any(|log| log.contains("owner_changed")));
+    assert_eq!(
+        get_owners(&solver_registry).await?,
+        (alice.id().clone(), None)
+    );
+
+    // The previous owner lost the ownership
+    let result = call_owner_method(
+        &owner,
+        &solver_registry,
+        "propose_owner",
+        json!({ "new_owner_id": owner.id() }),
+    )
+    .await?;
+    assert!(
+        !result.is_success(),
+        "Previous owner can't propose a new owner"
+    );
+
+    println!("Test passed: Ownership is transferred in two steps");
+    Ok(())
+}
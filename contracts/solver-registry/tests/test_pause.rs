@@ -0,0 +1,109 @@
+mod common;
+
+use common::utils::*;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_pause_and_revoke_all_worker_keys() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for emergency pause...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool, approve compose hash and register Alice as a worker
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Bob can pause only with the pauser role
+    let result = bob
+        .call(solver_registry.id(), "pause")
+        .args_json(json!({ "features": ["Ping"] }))
+        .transact()
+        .await?;
+    assert!(!result.is_success(), "Bob can't pause without the role");
+
+    let result = grant_role(&owner, &solver_registry, "Pauser", bob.id()).await?;
+    assert!(result.is_success(), "Owner can grant the pauser role");
+
+    let result = bob
+        .call(solver_registry.id(), "pause")
+        .args_json(json!({ "features": ["Ping"] }))
+        .transact()
+        .await?;
+    assert!(result.is_success(), "Pauser can pause");
+    let result = ping_worker(&alice, &solver_registry).await?;
+    assert!(!result.is_success(), "Ping should fail while paused");
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Ping is paused"));
+
+    let result = bob
+        .call(solver_registry.id(), "unpause")
+        .args_json(json!({ "features": ["Ping"] }))
+        .transact()
+        .await?;
+    assert!(result.is_success(), "Pauser can unpause");
+    let result = ping_worker(&alice, &solver_registry).await?;
+    assert!(result.is_success(), "Ping should succeed once unpaused");
+
+    // The keys which fail to be removed are reported and the workers are kept
+    let worker = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should be registered as a worker");
+    set_public_key_removal_failing(&mock_intents, &worker.public_key, true).await?;
+    let result = revoke_all_worker_keys(&bob, &solver_registry).await?;
+    assert!(
+        result.is_success(),
+        "Pauser can revoke all worker keys: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(result
+        .logs()
+        .iter()
+        .any(|log| log.contains("worker_key_revocation_failed")
+            && log.contains(&String::from(&worker.public_key))));
+    assert!(get_worker_info(&solver_registry, &alice).await?.is_some());
+
+    // Revoke the keys of all workers
+    set_public_key_removal_failing(&mock_intents, &worker.public_key, false).await?;
+    let result = revoke_all_worker_keys(&bob, &solver_registry).await?;
+    assert!(
+        result.is_success(),
+        "Pauser can revoke all worker keys: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(!result
+        .logs()
+        .iter()
+        .any(|log| log.contains("worker_key_revocation_failed")));
+
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert!(public_keys.is_empty(), "Worker key should be revoked");
+    assert!(get_worker_info(&solver_registry, &alice).await?.is_none());
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert!(pool.worker_id.is_none());
+
+    // Worker registration and deposits are paused until the pauser unpauses them
+    let paused_features: Vec<String> = solver_registry.view("get_paused_features").await?.json()?;
+    assert_eq!(paused_features, vec!["RegisterWorker", "Ping", "Deposit"]);
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Registration should fail while paused"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("RegisterWorker is paused"));
+
+    println!("Test passed: Workers are cut off from pool funds");
+    Ok(())
+}
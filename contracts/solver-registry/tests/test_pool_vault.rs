@@ -0,0 +1,245 @@
+mod common;
+
+use common::utils::*;
+use near_gas::NearGas;
+use near_sdk::{json_types::Base58CryptoHash, NearToken};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+#[tokio::test]
+async fn test_upgrade_pool_vaults() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool vault upgrades...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // New pools track the code hash of the published vault code
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    let vault_code_hash: Option<Base58CryptoHash> = solver_registry
+        .view("get_intents_vault_code_hash")
+        .await?
+        .json()?;
+    assert!(vault_code_hash.is_some());
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.vault_code_hash, vault_code_hash);
+
+    // Only the owner or an upgrader can upgrade the vaults
+    let result = alice
+        .call(solver_registry.id(), "upgrade_pool_vault")
+        .args_json(json!({ "pool_id": 0 }))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(!result.is_success(), "Upgrade should fail without the role");
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Missing role Upgrader"));
+
+    let result = owner
+        .call(solver_registry.id(), "upgrade_pool_vault")
+        .args_json(json!({ "pool_id": 0 }))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(result
+        .logs()
+        .iter()
+        .any(|log| log.contains("pool_vault_upgraded")));
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.vault_code_hash, vault_code_hash);
+
+    // The upgraded vault still manages the pool's keys in the intents contract
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let worker = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should be registered as a worker");
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert!(public_keys.contains(&worker.public_key));
+
+    // The batch upgrade skips the vaults already using the published code
+    let result = owner
+        .call(solver_registry.id(), "upgrade_all_pool_vaults")
+        .args_json(json!({}))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(!result
+        .logs()
+        .iter()
+        .any(|log| log.contains("pool_vault_upgraded")));
+
+    let result = owner
+        .call(solver_registry.id(), "upgrade_pool_vault")
+        .args_json(json!({ "pool_id": 2 }))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(!result.is_success(), "Upgrade should fail for unknown pool");
+
+    println!("Test passed: Pool vaults are upgraded by the registry");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upgrade_pool_vault_to_other_code() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for the upgrade of a pool vault to other code...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    let vault_code_hash: Option<Base58CryptoHash> = solver_registry
+        .view("get_intents_vault_code_hash")
+        .await?
+        .json()?;
+
+    // Publish another build of the vault as a global contract
+    let wasm = std::fs::read(INTENTS_VAULT_TEST_UPGRADE_WASM).expect("Contract wasm not found");
+    let hash: [u8; 32] = Sha256::digest(&wasm).into();
+    let test_upgrade_code_hash = Base58CryptoHash::from(hash);
+    assert_ne!(Some(test_upgrade_code_hash), vault_code_hash);
+    let result = mock_intents
+        .call("deploy_global_contract")
+        .args(wasm)
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Only the registry can upgrade the vault, which is migrated to the other code
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    let result = alice
+        .call(&pool_account_id, "upgrade")
+        .args_json(json!({ "code_hash": test_upgrade_code_hash }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Only the registry can upgrade the vault"
+    );
+
+    let result = solver_registry
+        .as_account()
+        .call(&pool_account_id, "upgrade")
+        .args_json(json!({ "code_hash": test_upgrade_code_hash }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(result
+        .logs()
+        .iter()
+        .any(|log| log.contains("Migrated to the test upgrade")));
+
+    // The migrated vault still manages the pool's keys in the intents contract
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let worker = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should be registered as a worker");
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert!(public_keys.contains(&worker.public_key));
+
+    // The registry upgrades the vault back to the published code
+    let result = owner
+        .call(solver_registry.id(), "upgrade_pool_vault")
+        .args_json(json!({ "pool_id": 0 }))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(result
+        .logs()
+        .iter()
+        .any(|log| log.contains("pool_vault_upgraded")));
+    assert!(!result
+        .logs()
+        .iter()
+        .any(|log| log.contains("Migrated to the test upgrade")));
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.vault_code_hash, vault_code_hash);
+
+    println!("Test passed: Pool vaults are upgraded to other code and migrated");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_publish_intents_vault_code() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for publishing the intents vault code...");
+    let sandbox = near_workspaces::sandbox().await?;
+    let (_wnear, _usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Only the owner or an upgrader can publish the code
+    let result = alice
+        .call(solver_registry.id(), "publish_intents_vault_code")
+        .deposit(NearToken::from_near(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Publishing should fail without the role"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Missing role Upgrader"));
+
+    // The code bundled with the registry is published once
+    let result = publish_intents_vault_code(&owner, &solver_registry).await?;
+    assert!(
+        !result.is_success(),
+        "Publishing the same code again should fail"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Intents vault code already published"));
+
+    println!("Test passed: The intents vault code is published as a global contract");
+
+    Ok(())
+}
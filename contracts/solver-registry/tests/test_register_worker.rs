@@ -26,8 +26,10 @@ async fn test_register_one_worker() -> Result<(), Box<dyn std::error::Error>> {
         pool.token_ids, pool.amounts, pool.fee, pool.shares_total_supply
     );
 
-    // Approve compose hash
+    // Approve compose hash and the pre-launch script of the samples
     approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
 
     // Register worker (Alice)
     println!("Registering worker (Alice)...");
@@ -92,8 +94,10 @@ async fn test_worker_registration_with_invalid_tee_data() -> Result<(), Box<dyn
     // Create a liquidity pool
     create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
 
-    // Approve compose hash
+    // Approve compose hash and the pre-launch script of the samples
     approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
 
     // Try to register worker with invalid quote hex (empty string)
     println!("Attempting to register worker with invalid quote hex...");
@@ -135,8 +139,10 @@ async fn test_worker_registration_requires_sufficient_deposit(
     // Create a liquidity pool
     create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
 
-    // Approve compose hash
+    // Approve compose hash and the pre-launch script of the samples
     approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
 
     // Try to register worker with insufficient deposit (0 yoctoNEAR)
     println!("Attempting to register worker with insufficient deposit...");
@@ -147,7 +153,8 @@ async fn test_worker_registration_requires_sufficient_deposit(
             "quote_hex": QUOTE_HEX_ALICE.to_string(),
             "collateral": QUOTE_COLLATERAL_ALICE.to_string(),
             "checksum": CHECKSUM_ALICE.to_string(),
-            "tcb_info": TCB_INFO_ALICE.to_string()
+            "tcb_info": TCB_INFO_ALICE.to_string(),
+            "intents_public_key": alice.secret_key().public_key().to_string()
         }))
         .deposit(NearToken::from_yoctonear(0)) // No deposit
         .gas(NearGas::from_tgas(300))
@@ -175,11 +182,12 @@ async fn test_worker_registration_without_compose_hash_approval(
     let sandbox = near_workspaces::sandbox().await?;
 
     // Setup test environment
-    let (wnear, usdc, _owner, alice, _bob, _mock_intents, solver_registry) =
+    let (wnear, usdc, owner, alice, _bob, _mock_intents, solver_registry) =
         setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
 
     // Create a liquidity pool
     create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
 
     // Try to register worker without approving compose hash first
     println!("Attempting to register worker without compose hash approval...");
@@ -199,6 +207,399 @@ async fn test_worker_registration_without_compose_hash_approval(
     Ok(())
 }
 
+#[tokio::test]
+async fn test_worker_registration_without_pre_launch_script_approval(
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for worker registration without pre-launch script approval...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Approve compose hash without approving the pre-launch script
+    approve_compose_hash(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+
+    // Approve a different pre-launch script
+    let result = approve_pre_launch_script(&owner, &solver_registry, &"00".repeat(32)).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Registration should fail since the pre-launch script of the worker is not approved
+    println!("Attempting to register worker with unapproved pre-launch script...");
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Worker registration should fail without pre-launch script approval"
+    );
+
+    let error = result.into_result().unwrap_err();
+    println!("Expected error received: {:?}", error);
+
+    // Registration should succeed once the pre-launch script is approved
+    let result =
+        approve_pre_launch_script(&owner, &solver_registry, PRE_LAUNCH_SCRIPT_HASH).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    println!("Test passed: Worker registration requires pre-launch script approval");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_worker_registration_with_allowed_envs() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for worker registration with allowed environment variables...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Approve compose hash and the pre-launch script of the samples
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+
+    // Only allow a subset of the environment variables of the worker
+    let result = set_compose_hash_allowed_envs(
+        &owner,
+        &solver_registry,
+        Some(ALLOWED_ENVS[..ALLOWED_ENVS.len() - 1].to_vec()),
+    )
+    .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Registration should fail since the worker allows an environment variable out of the allowlist
+    println!("Attempting to register worker with disallowed environment variables...");
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Worker registration should fail with disallowed environment variables"
+    );
+
+    let error = result.into_result().unwrap_err();
+    println!("Expected error received: {:?}", error);
+
+    // Registration should succeed once all environment variables are allowed
+    let result =
+        set_compose_hash_allowed_envs(&owner, &solver_registry, Some(ALLOWED_ENVS.to_vec()))
+            .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Verify the allowed environment variables are recorded with the worker
+    let worker_info = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should be registered as a worker");
+    assert_eq!(worker_info.allowed_envs, ALLOWED_ENVS.to_vec());
+
+    println!("Test passed: Worker registration validates allowed environment variables");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_worker_registration_with_docker_image_hashes(
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for worker registration with approved docker image hashes...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Approve compose hash along with the pre-launch script and the docker image of the samples
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+    let result =
+        update_docker_image_hash(&owner, &solver_registry, DOCKER_IMAGE_HASH, true).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Registration should fail since the approved docker image can't be verified without the
+    // docker image hash event
+    println!("Attempting to register worker without docker image hash event...");
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Worker registration should fail without docker image hash event"
+    );
+    let error = result.into_result().unwrap_err();
+    println!("Expected error received: {:?}", error);
+
+    // Registration should fail since the sample CVM doesn't measure the configured event
+    let result =
+        set_docker_image_hash_event(&owner, &solver_registry, Some("solver-image-digest")).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Worker registration should fail without the measured event"
+    );
+
+    // Registration should fail since the measured digest isn't approved
+    let result =
+        set_docker_image_hash_event(&owner, &solver_registry, Some(MEASURED_IMAGE_HASH_EVENT))
+            .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Worker registration should fail with an unapproved digest"
+    );
+
+    // Registration should still fail once no docker image is approved for the compose hash
+    let result =
+        update_docker_image_hash(&owner, &solver_registry, DOCKER_IMAGE_HASH, false).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Worker registration should fail without approved docker images"
+    );
+
+    // Registration should succeed once the measured digest is approved
+    let result =
+        update_docker_image_hash(&owner, &solver_registry, MEASURED_IMAGE_HASH, true).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    println!("Test passed: Worker registration verifies approved docker image hashes");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_worker_registration_with_registration_challenge(
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for worker registration with registration challenge...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Approve compose hash and the pre-launch script of the samples
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+
+    // Registration challenges are required by default
+    assert!(!solver_registry
+        .view("is_legacy_report_data_allowed")
+        .await?
+        .json::<bool>()?);
+
+    // Registration should fail without a registration challenge
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Worker registration should fail without registration challenge"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Registration challenge not found"));
+
+    // Registration challenge can't be requested for a non-existent pool
+    let result = request_registration_challenge(&alice, &solver_registry, 1).await?;
+    assert!(
+        !result.is_success(),
+        "Registration challenge should not be issued for a non-existent pool"
+    );
+
+    // Request a registration challenge
+    let result = request_registration_challenge(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let challenge = get_registration_challenge(&solver_registry, &alice)
+        .await?
+        .expect("Alice should have a pending registration challenge");
+    assert_eq!(challenge.pool_id, 0);
+    assert_eq!(challenge.nonce.len(), 64);
+
+    // Registration should fail since the sample quote doesn't include the nonce in its report data
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Worker registration should fail without the nonce in the report data"
+    );
+    let error = result.into_result().unwrap_err();
+    println!("Expected error received: {:?}", error);
+
+    // The challenge is still pending since the failed registration was reverted
+    assert!(get_registration_challenge(&solver_registry, &alice)
+        .await?
+        .is_some());
+
+    println!("Test passed: Worker registration requires the registration challenge");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_worker_registration_with_other_intents_key() -> Result<(), Box<dyn std::error::Error>>
+{
+    println!("Starting test for worker registration with another intents public key...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Approve compose hash and the pre-launch script of the samples
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+
+    // Report data V1 only commits to the signer's public key, so the intents public key can't be
+    // separated from it
+    let result = register_worker_with_intents_key(
+        &alice,
+        &solver_registry,
+        0,
+        QUOTE_HEX_ALICE,
+        QUOTE_COLLATERAL_ALICE,
+        CHECKSUM_ALICE,
+        TCB_INFO_ALICE,
+        &bob.secret_key().public_key().to_string(),
+    )
+    .await?;
+    assert!(
+        !result.is_success(),
+        "Worker registration should fail with another intents public key"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Intents public key must be the signer's public key"));
+
+    println!("Test passed: Worker registration validates the intents public key");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_worker_registration_with_allowed_key_types() -> Result<(), Box<dyn std::error::Error>>
+{
+    println!("Starting test for worker registration with allowed key types...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Approve compose hash and the pre-launch script of the samples
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+
+    // At least one key type must be allowed
+    let result = set_allowed_key_types(&owner, &solver_registry, &[]).await?;
+    assert!(
+        !result.is_success(),
+        "Setting no allowed key types should fail"
+    );
+
+    // Only allow secp256k1 keys, while Alice's key is ed25519
+    let result = set_allowed_key_types(&owner, &solver_registry, &["Secp256k1"]).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Worker registration should fail with a disallowed key type"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Key type not allowed"));
+
+    // Registration should succeed once ed25519 keys are allowed
+    let result = set_allowed_key_types(&owner, &solver_registry, &["Ed25519"]).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    println!("Test passed: Worker registration validates the key type");
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_approve_compose_hash_with_non_owner() -> Result<(), Box<dyn std::error::Error>> {
     println!("Starting test for compose hash approval with non-owner...");
@@ -247,8 +648,10 @@ async fn test_worker_registration_with_invalid_pool_id() -> Result<(), Box<dyn s
     // Create a liquidity pool (pool_id = 0)
     create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
 
-    // Approve compose hash
+    // Approve compose hash and the pre-launch script of the samples
     approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
 
     // Try to register worker with non-existent pool ID
     println!("Attempting to register worker with non-existent pool ID...");
@@ -287,14 +690,16 @@ async fn test_multiple_pools_worker_registration() -> Result<(), Box<dyn std::er
             "token_ids": [wnear.id(), usdc.id()],
             "fee": 500
         }))
-        .deposit(NearToken::from_yoctonear(1_500_000_000_000_000_000_000_000)) // 1.5 NEAR
+        .deposit(NearToken::from_millinear(100)) // 0.1 NEAR
         .gas(NearGas::from_tgas(300))
         .transact()
         .await?;
     assert!(result.is_success(), "Second pool creation should succeed");
 
-    // Approve compose hash
+    // Approve compose hash and the pre-launch script of the samples
     approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
 
     // Register Alice as worker for pool 0
     println!("Registering Alice as worker for pool 0...");
@@ -355,8 +760,10 @@ async fn test_worker_registration_edge_cases() -> Result<(), Box<dyn std::error:
     // Create a liquidity pool
     create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
 
-    // Approve compose hash
+    // Approve compose hash and the pre-launch script of the samples
     approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
 
     // Test 1: Register with valid pool ID
     println!("Testing registration with valid pool ID...");
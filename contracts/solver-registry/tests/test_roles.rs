@@ -0,0 +1,82 @@
+mod common;
+
+use common::constants::*;
+use common::utils::*;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_compose_hash_manager_role() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for role-based access control...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (_wnear, _usdc, owner, alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // The owner implicitly holds all roles
+    assert!(has_role(&solver_registry, "ComposeHashManager", owner.id()).await?);
+    assert!(has_role(&solver_registry, "Upgrader", owner.id()).await?);
+    assert!(!has_role(&solver_registry, "ComposeHashManager", alice.id()).await?);
+
+    // Alice can't approve compose hashes without the role
+    let result = alice
+        .call(solver_registry.id(), "approve_compose_hash")
+        .args_json(json!({ "compose_hash": COMPOSE_HASH }))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Approval should fail without the role"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Missing role ComposeHashManager"));
+
+    // Only the owner can grant roles
+    let result = grant_role(&bob, &solver_registry, "ComposeHashManager", alice.id()).await?;
+    assert!(!result.is_success(), "Non-owner can't grant roles");
+
+    let result = grant_role(&owner, &solver_registry, "ComposeHashManager", alice.id()).await?;
+    assert!(result.is_success(), "Owner can grant roles");
+    assert!(result.logs().iter().any(|log| log.contains("role_granted")));
+    assert!(has_role(&solver_registry, "ComposeHashManager", alice.id()).await?);
+    assert_eq!(
+        get_role_members(&solver_registry, "ComposeHashManager").await?,
+        vec![alice.id().clone()]
+    );
+
+    let result = grant_role(&owner, &solver_registry, "ComposeHashManager", alice.id()).await?;
+    assert!(!result.is_success(), "Role can't be granted twice");
+
+    // Alice approves compose hashes with the role, but can't upgrade the contract
+    approve_compose_hash(&alice, &solver_registry).await?;
+    let result = alice
+        .call(solver_registry.id(), "upgrade")
+        .args(vec![])
+        .transact()
+        .await?;
+    assert!(!result.is_success(), "Upgrade should fail without the role");
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Missing role Upgrader"));
+
+    // Alice loses access once the role is revoked
+    let result = revoke_role(&owner, &solver_registry, "ComposeHashManager", alice.id()).await?;
+    assert!(result.is_success(), "Owner can revoke roles");
+    assert!(result.logs().iter().any(|log| log.contains("role_revoked")));
+    assert!(!has_role(&solver_registry, "ComposeHashManager", alice.id()).await?);
+    assert!(get_role_members(&solver_registry, "ComposeHashManager")
+        .await?
+        .is_empty());
+
+    let result = alice
+        .call(solver_registry.id(), "remove_compose_hash")
+        .args_json(json!({ "compose_hash": COMPOSE_HASH }))
+        .transact()
+        .await?;
+    assert!(!result.is_success(), "Removal should fail without the role");
+
+    let result = revoke_role(&owner, &solver_registry, "ComposeHashManager", alice.id()).await?;
+    assert!(!result.is_success(), "Role can't be revoked twice");
+
+    println!("Test passed: Roles control access to registry administration");
+    Ok(())
+}
@@ -0,0 +1,268 @@
+mod common;
+
+use common::constants::*;
+use common::utils::*;
+use near_sdk::PublicKey;
+
+#[tokio::test]
+async fn test_rotate_worker_key_validation() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for worker key rotation validation...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Approve compose hash and the pre-launch script of the samples
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+
+    // Unregistered worker can't rotate its key
+    let result = rotate_worker_key(
+        &bob,
+        &solver_registry,
+        &alice.secret_key().public_key().to_string(),
+        QUOTE_HEX_BOB,
+        QUOTE_COLLATERAL_BOB,
+        TCB_INFO_BOB,
+    )
+    .await?;
+    assert!(
+        !result.is_success(),
+        "Key rotation should fail for an unregistered worker"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Worker not found"));
+
+    // Register Alice as a worker
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let worker = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should be registered as a worker");
+
+    // Rotation to the current key is rejected
+    let result = rotate_worker_key(
+        &alice,
+        &solver_registry,
+        &worker.public_key.to_string(),
+        QUOTE_HEX_ALICE,
+        QUOTE_COLLATERAL_ALICE,
+        TCB_INFO_ALICE,
+    )
+    .await?;
+    assert!(
+        !result.is_success(),
+        "Key rotation should fail with the current key"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("New public key must be different"));
+
+    // Report data V1 of the sample quote doesn't commit to the new key
+    let result = rotate_worker_key(
+        &alice,
+        &solver_registry,
+        &bob.secret_key().public_key().to_string(),
+        QUOTE_HEX_ALICE,
+        QUOTE_COLLATERAL_ALICE,
+        TCB_INFO_ALICE,
+    )
+    .await?;
+    assert!(
+        !result.is_success(),
+        "Key rotation should fail without an attestation committing to the new key"
+    );
+
+    // The worker keeps its key in the registry and the intents contract
+    let worker_after = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should still be registered as a worker");
+    assert_eq!(worker_after.public_key, worker.public_key);
+
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert_eq!(public_keys.len(), 1);
+    assert!(public_keys.contains(&worker.public_key));
+
+    println!("Test passed: Worker key rotation is validated");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rotate_worker_key() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for worker key rotation...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+
+    // Register Alice as a worker
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let old_public_key = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should be registered as a worker")
+        .public_key;
+
+    // Alice signs with the key of Bob's sample quote, whose report data V1 commits to it
+    let alice_with_new_key = add_access_key(&sandbox, &alice, bob.secret_key()).await?;
+    let new_public_key: PublicKey = bob.secret_key().public_key().to_string().parse()?;
+    let result = rotate_worker_key(
+        &alice_with_new_key,
+        &solver_registry,
+        &new_public_key.to_string(),
+        QUOTE_HEX_BOB,
+        QUOTE_COLLATERAL_BOB,
+        TCB_INFO_BOB,
+    )
+    .await?;
+    assert!(
+        result.is_success(),
+        "Key rotation should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // The new key replaces the old one in the registry and the intents contract
+    let worker = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should still be registered as a worker");
+    assert_eq!(worker.public_key, new_public_key);
+
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert_eq!(public_keys.len(), 1);
+    assert!(public_keys.contains(&new_public_key));
+    assert!(!public_keys.contains(&old_public_key));
+
+    println!("Test passed: Worker key is rotated");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rotate_worker_key_rollback() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for worker key rotation rollback...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let old_public_key = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should be registered as a worker")
+        .public_key;
+
+    let alice_with_new_key = add_access_key(&sandbox, &alice, bob.secret_key()).await?;
+    let new_public_key: PublicKey = bob.secret_key().public_key().to_string().parse()?;
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+
+    // The old key can't be removed, so the new key is removed again
+    set_public_key_removal_failing(&mock_intents, &old_public_key, true).await?;
+    rotate_worker_key(
+        &alice_with_new_key,
+        &solver_registry,
+        &new_public_key.to_string(),
+        QUOTE_HEX_BOB,
+        QUOTE_COLLATERAL_BOB,
+        TCB_INFO_BOB,
+    )
+    .await?;
+
+    let worker = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should still be registered as a worker");
+    assert_eq!(worker.public_key, old_public_key);
+
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert_eq!(public_keys.len(), 1);
+    assert!(public_keys.contains(&old_public_key));
+    assert!(get_orphaned_worker_keys(&solver_registry).await?.is_empty());
+
+    // The new key can't be removed either, so it's recorded as orphaned
+    set_public_key_removal_failing(&mock_intents, &new_public_key, true).await?;
+    rotate_worker_key(
+        &alice_with_new_key,
+        &solver_registry,
+        &new_public_key.to_string(),
+        QUOTE_HEX_BOB,
+        QUOTE_COLLATERAL_BOB,
+        TCB_INFO_BOB,
+    )
+    .await?;
+
+    let worker = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should still be registered as a worker");
+    assert_eq!(worker.public_key, old_public_key);
+
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert_eq!(public_keys.len(), 2);
+    assert_eq!(
+        get_orphaned_worker_keys(&solver_registry).await?,
+        vec![(new_public_key.clone(), 0)]
+    );
+
+    // The orphaned key is revoked separately from the worker's key
+    set_public_key_removal_failing(&mock_intents, &old_public_key, false).await?;
+    set_public_key_removal_failing(&mock_intents, &new_public_key, false).await?;
+    let result = revoke_orphaned_worker_keys(&alice, &solver_registry).await?;
+    assert!(
+        !result.is_success(),
+        "Non-pauser can't revoke orphaned keys"
+    );
+    let result = revoke_orphaned_worker_keys(&owner, &solver_registry).await?;
+    assert!(
+        result.is_success(),
+        "Owner can revoke orphaned worker keys: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert_eq!(public_keys.len(), 1);
+    assert!(public_keys.contains(&old_public_key));
+    assert!(get_orphaned_worker_keys(&solver_registry).await?.is_empty());
+    assert!(get_worker_info(&solver_registry, &alice).await?.is_some());
+
+    let result = revoke_all_worker_keys(&owner, &solver_registry).await?;
+    assert!(
+        result.is_success(),
+        "Owner can revoke all worker keys: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert!(public_keys.is_empty());
+    assert!(get_worker_info(&solver_registry, &alice).await?.is_none());
+
+    println!("Test passed: Worker key rotation is rolled back");
+    Ok(())
+}
@@ -14,8 +14,10 @@ async fn test_only_one_active_worker_per_pool() -> Result<(), Box<dyn std::error
     // Create a liquidity pool
     create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
 
-    // Approve compose hash
+    // Approve compose hash and the pre-launch script of the samples
     approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
 
     // Get pool account ID before worker registration
     let pool_account_id = get_pool_account_id(&solver_registry, 0);
@@ -126,8 +128,10 @@ async fn test_worker_ping_functionality() -> Result<(), Box<dyn std::error::Erro
     // Create a liquidity pool
     create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
 
-    // Approve compose hash
+    // Approve compose hash and the pre-launch script of the samples
     approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
 
     // Get pool account ID
     let pool_account_id = get_pool_account_id(&solver_registry, 0);
@@ -293,8 +297,10 @@ async fn test_worker_replacement_after_timeout() -> Result<(), Box<dyn std::erro
     // Create a liquidity pool
     create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
 
-    // Approve compose hash
+    // Approve compose hash and the pre-launch script of the samples
     approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
 
     // Get pool account ID
     let pool_account_id = get_pool_account_id(&solver_registry, 0);
@@ -442,8 +448,10 @@ async fn test_worker_cannot_register_while_active_worker_is_pinging(
     // Create a liquidity pool
     create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
 
-    // Approve compose hash
+    // Approve compose hash and the pre-launch script of the samples
     approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
 
     // Register Alice as the first worker
     println!("Registering Alice as the first worker...");
@@ -669,8 +677,10 @@ async fn test_worker_can_register_after_inactive_worker_timeout(
     // Create a liquidity pool
     create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
 
-    // Approve compose hash
+    // Approve compose hash and the pre-launch script of the samples
     approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
 
     // Register Alice as the first worker
     println!("Registering Alice as the first worker...");
@@ -902,3 +912,129 @@ async fn test_worker_ping_without_registration() -> Result<(), Box<dyn std::erro
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_set_worker_ping_timeout() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for the global worker ping timeout...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Only the owner can set the global timeout
+    let result = set_worker_ping_timeout_ms(&alice, &solver_registry, 30 * 1000).await?;
+    assert!(
+        !result.is_success(),
+        "Setting the timeout should fail for non-owner"
+    );
+
+    let result = set_worker_ping_timeout_ms(&owner, &solver_registry, 0).await?;
+    assert!(!result.is_success(), "Zero timeout should be rejected");
+
+    let result = set_worker_ping_timeout_ms(&owner, &solver_registry, 30 * 1000).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(result
+        .logs()
+        .iter()
+        .any(|log| log.contains("worker_ping_timeout_updated")));
+
+    let worker_ping_timeout_ms: u64 = solver_registry
+        .view("get_worker_ping_timeout_ms")
+        .await?
+        .json()?;
+    assert_eq!(worker_ping_timeout_ms, 30 * 1000);
+
+    // Pools without an override use the global timeout
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.worker_ping_timeout_ms, None);
+    assert_eq!(pool.effective_worker_ping_timeout_ms, 30 * 1000);
+
+    println!("Test passed: The owner sets the global worker ping timeout");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_worker_replacement_after_pool_timeout() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for the worker ping timeout override of a pool...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment with a long global timeout
+    let (wnear, usdc, owner, alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+
+    // Only the owner or a pool manager can override the timeout of a pool
+    let result =
+        set_pool_worker_ping_timeout_ms(&alice, &solver_registry, 0, Some(5 * 1000)).await?;
+    assert!(
+        !result.is_success(),
+        "Overriding the timeout should fail without the role"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Missing role PoolManager"));
+
+    let result =
+        set_pool_worker_ping_timeout_ms(&owner, &solver_registry, 0, Some(5 * 1000)).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(result
+        .logs()
+        .iter()
+        .any(|log| log.contains("pool_worker_ping_timeout_updated")));
+
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.worker_ping_timeout_ms, Some(5 * 1000));
+    assert_eq!(pool.effective_worker_ping_timeout_ms, 5 * 1000);
+
+    // Register Alice, then Bob can't replace her while she's active
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = register_worker_bob(&bob, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Bob should not replace the active worker"
+    );
+
+    // Bob replaces Alice after the timeout of the pool, before the global timeout
+    wait_for_worker_timeout(5).await;
+    let result = register_worker_bob(&bob, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Bob should replace Alice after the timeout of the pool: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.worker_id.as_ref(), Some(bob.id()));
+
+    // Removing the override falls back to the global timeout
+    let result = set_pool_worker_ping_timeout_ms(&owner, &solver_registry, 0, None).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.worker_ping_timeout_ms, None);
+    assert_eq!(pool.effective_worker_ping_timeout_ms, 10 * 60 * 1000);
+
+    println!("Test passed: The worker ping timeout of a pool overrides the global timeout");
+
+    Ok(())
+}
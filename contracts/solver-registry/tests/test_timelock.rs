@@ -0,0 +1,260 @@
+mod common;
+
+use common::constants::*;
+use common::utils::*;
+use near_workspaces::{result::ExecutionFinalResult, Account, Contract};
+use serde_json::json;
+
+async fn call(
+    account: &Account,
+    solver_registry: &Contract,
+    method: &str,
+    args: serde_json::Value,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = account
+        .call(solver_registry.id(), method)
+        .args_json(args)
+        .transact()
+        .await?;
+    print_logs(&result);
+    Ok(result)
+}
+
+async fn get_approved_compose_hashes(
+    solver_registry: &Contract,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    Ok(solver_registry
+        .view("get_approved_compose_hashes")
+        .await?
+        .json()?)
+}
+
+#[tokio::test]
+async fn test_timelocked_compose_hash_approval() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for timelocked compose hash approval...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (_wnear, _usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // The delay is set immediately while the timelock is disabled
+    let result = call(
+        &owner,
+        &solver_registry,
+        "set_timelock_delay_ms",
+        json!({ "timelock_delay_ms": 2000 }),
+    )
+    .await?;
+    assert!(result.is_success(), "Owner can set the timelock delay");
+    let timelock_delay_ms: u64 = solver_registry
+        .view("get_timelock_delay_ms")
+        .await?
+        .json()?;
+    assert_eq!(timelock_delay_ms, 2000);
+
+    // The compose hash approval is scheduled
+    let approve_args = json!({ "compose_hash": COMPOSE_HASH });
+    let result = call(
+        &owner,
+        &solver_registry,
+        "approve_compose_hash",
+        approve_args.clone(),
+    )
+    .await?;
+    assert!(result.is_success(), "Owner can schedule the approval");
+    assert!(result
+        .logs()
+        .iter()
+        .any(|log| log.contains("action_scheduled")));
+    let action_id: Option<u64> = result.json()?;
+    assert_eq!(action_id, Some(0));
+    assert!(get_approved_compose_hashes(&solver_registry)
+        .await?
+        .is_empty());
+
+    // The action can't be executed before the delay
+    let result = call(
+        &owner,
+        &solver_registry,
+        "execute_action",
+        json!({ "action_id": 0 }),
+    )
+    .await?;
+    assert!(
+        !result.is_success(),
+        "Action can't be executed before the delay"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Timelock delay not passed"));
+
+    // Only the owner or a compose hash manager can cancel the action
+    let result = call(
+        &alice,
+        &solver_registry,
+        "cancel_action",
+        json!({ "action_id": 0 }),
+    )
+    .await?;
+    assert!(!result.is_success(), "Alice can't cancel the action");
+    let result = call(
+        &owner,
+        &solver_registry,
+        "cancel_action",
+        json!({ "action_id": 0 }),
+    )
+    .await?;
+    assert!(result.is_success(), "Owner can cancel the action");
+    assert!(result
+        .logs()
+        .iter()
+        .any(|log| log.contains("action_cancelled")));
+
+    // Schedule again and execute after the delay
+    let result = call(
+        &owner,
+        &solver_registry,
+        "approve_compose_hash",
+        approve_args,
+    )
+    .await?;
+    let action_id: Option<u64> = result.json()?;
+    assert_eq!(action_id, Some(1));
+    let scheduled_actions: Vec<serde_json::Value> = solver_registry
+        .view("get_scheduled_actions")
+        .args_json(json!({ "offset": 0, "limit": 10 }))
+        .await?
+        .json()?;
+    assert_eq!(scheduled_actions.len(), 1);
+
+    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+
+    let result = call(
+        &alice,
+        &solver_registry,
+        "execute_action",
+        json!({ "action_id": 1 }),
+    )
+    .await?;
+    assert!(!result.is_success(), "Alice can't execute the action");
+    let result = call(
+        &owner,
+        &solver_registry,
+        "execute_action",
+        json!({ "action_id": 1 }),
+    )
+    .await?;
+    assert!(
+        result.is_success(),
+        "Owner can execute the action after the delay: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert_eq!(
+        get_approved_compose_hashes(&solver_registry).await?,
+        vec![COMPOSE_HASH.to_string()]
+    );
+
+    // Changing the delay is timelocked as well
+    let result = call(
+        &owner,
+        &solver_registry,
+        "set_timelock_delay_ms",
+        json!({ "timelock_delay_ms": 0 }),
+    )
+    .await?;
+    assert!(result.is_success(), "Owner can schedule the delay change");
+    let timelock_delay_ms: u64 = solver_registry
+        .view("get_timelock_delay_ms")
+        .await?
+        .json()?;
+    assert_eq!(timelock_delay_ms, 2000);
+
+    println!("Test passed: Compose hash approval is timelocked");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_timelocked_verification_settings() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for timelocked verification settings...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (_wnear, _usdc, owner, _alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Approve the compose hash and a docker image before enabling the timelock
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let result =
+        update_docker_image_hash(&owner, &solver_registry, DOCKER_IMAGE_HASH, true).await?;
+    assert!(result.is_success(), "Owner can approve a docker image hash");
+
+    let result = call(
+        &owner,
+        &solver_registry,
+        "set_timelock_delay_ms",
+        json!({ "timelock_delay_ms": 2000 }),
+    )
+    .await?;
+    assert!(result.is_success(), "Owner can set the timelock delay");
+
+    // Loosening the verification is scheduled instead of taking effect
+    let result = set_allow_legacy_report_data(&owner, &solver_registry, true).await?;
+    assert!(
+        result.is_success(),
+        "Owner can schedule allowing legacy report data"
+    );
+    let legacy_action_id: Option<u64> = result.json()?;
+    assert_eq!(legacy_action_id, Some(0));
+    let result =
+        update_docker_image_hash(&owner, &solver_registry, DOCKER_IMAGE_HASH, false).await?;
+    assert!(
+        result.is_success(),
+        "Owner can schedule the docker image removal"
+    );
+    let removal_action_id: Option<u64> = result.json()?;
+    assert_eq!(removal_action_id, Some(1));
+
+    let is_legacy_report_data_allowed: bool = solver_registry
+        .view("is_legacy_report_data_allowed")
+        .await?
+        .json()?;
+    assert!(!is_legacy_report_data_allowed);
+    let config: serde_json::Value = solver_registry
+        .view("get_compose_hash_config")
+        .args_json(json!({ "compose_hash": COMPOSE_HASH }))
+        .await?
+        .json()?;
+    assert_eq!(config["docker_image_hashes"], json!([DOCKER_IMAGE_HASH]));
+
+    // Both take effect once executed after the delay
+    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+    for action_id in [0, 1] {
+        let result = call(
+            &owner,
+            &solver_registry,
+            "execute_action",
+            json!({ "action_id": action_id }),
+        )
+        .await?;
+        assert!(
+            result.is_success(),
+            "Owner can execute the action after the delay: {:#?}",
+            result.into_result().unwrap_err()
+        );
+    }
+
+    let is_legacy_report_data_allowed: bool = solver_registry
+        .view("is_legacy_report_data_allowed")
+        .await?
+        .json()?;
+    assert!(is_legacy_report_data_allowed);
+    let config: serde_json::Value = solver_registry
+        .view("get_compose_hash_config")
+        .args_json(json!({ "compose_hash": COMPOSE_HASH }))
+        .await?
+        .json()?;
+    assert_eq!(config["docker_image_hashes"], json!([]));
+
+    println!("Test passed: Verification settings are timelocked");
+    Ok(())
+}
@@ -0,0 +1,428 @@
+use near_gas::NearGas;
+use near_sdk::{json_types::Base58CryptoHash, NearToken};
+use near_workspaces::{network::Sandbox, result::ExecutionFinalResult, Account, Contract, Worker};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+mod common;
+
+use common::constants::*;
+use common::utils::*;
+
+/// Code of the initial contract version, with the state layout of version 0
+const SOLVER_REGISTRY_V0_CONTRACT_WASM: &str = "tests/samples/legacy/solver_registry_v0.wasm";
+
+async fn deploy_solver_registry_v0(
+    sandbox: &Worker<Sandbox>,
+    intents_contract: &Contract,
+    owner: &Account,
+) -> Result<Contract, Box<dyn std::error::Error>> {
+    let wasm = std::fs::read(SOLVER_REGISTRY_V0_CONTRACT_WASM).expect("Contract wasm not found");
+    let account = create_account(sandbox, "solver-registry", 100).await?;
+    let solver_registry = account.deploy(&wasm).await?.result;
+
+    let result = solver_registry
+        .call("new")
+        .args_json(json!({
+            "owner_id": owner.id(),
+            "intents_contract_id": intents_contract.id(),
+            "worker_ping_timeout_ms": 10 * 60 * 1000
+        }))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    Ok(solver_registry)
+}
+
+/// Creates pools with the deposit of the initial version, which deploys the vault code to each
+/// pool's account
+async fn create_liquidity_pools_v0(
+    solver_registry: &Contract,
+    wnear: &Contract,
+    usdc: &Contract,
+    count: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for _ in 0..count {
+        let result = solver_registry
+            .call("create_liquidity_pool")
+            .args_json(json!({
+                "token_ids": [wnear.id(), usdc.id()],
+                "fee": 300
+            }))
+            .deposit(NearToken::from_millinear(1500))
+            .gas(NearGas::from_tgas(300))
+            .transact()
+            .await?;
+        assert!(
+            result.is_success(),
+            "{:#?}",
+            result.into_result().unwrap_err()
+        );
+    }
+    Ok(())
+}
+
+async fn migrate_pools(
+    account: &Account,
+    solver_registry: &Contract,
+    limit: Option<u32>,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = account
+        .call(solver_registry.id(), "migrate_pools")
+        .args_json(json!({ "limit": limit }))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    print_logs(&result);
+    Ok(result)
+}
+
+async fn upgrade(
+    owner: &Account,
+    solver_registry: &Contract,
+    wasm: &[u8],
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "upgrade")
+        .args(wasm.to_vec())
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    print_logs(&result);
+    Ok(result)
+}
+
+fn code_hash(wasm: &[u8]) -> Base58CryptoHash {
+    let hash: [u8; 32] = Sha256::digest(wasm).into();
+    Base58CryptoHash::from(hash)
+}
+
+async fn stage_upgrade(
+    owner: &Account,
+    solver_registry: &Contract,
+    wasm: &[u8],
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let code_hash = code_hash(wasm);
+    let result = owner
+        .call(solver_registry.id(), "stage_upgrade")
+        .args_json(json!({ "code_hash": code_hash }))
+        .transact()
+        .await?;
+    Ok(result)
+}
+
+async fn get_state_version(solver_registry: &Contract) -> Result<u32, Box<dyn std::error::Error>> {
+    Ok(solver_registry.view("get_state_version").await?.json()?)
+}
+
+#[tokio::test]
+async fn test_upgrade_from_v0_state() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for upgrade from the state version 0...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    let (wnear, usdc) = create_test_tokens(&sandbox).await?;
+    let (owner, alice, _bob) = create_test_accounts(&sandbox).await?;
+    register_accounts_for_tokens(&wnear, &usdc, &[&alice]).await?;
+    let mock_intents = deploy_mock_intents(&sandbox).await?;
+
+    println!("Deploying the initial Solver Registry contract...");
+    let solver_registry = deploy_solver_registry_v0(&sandbox, &mock_intents, &owner).await?;
+    register_accounts_for_tokens(
+        &wnear,
+        &usdc,
+        &[mock_intents.as_account(), solver_registry.as_account()],
+    )
+    .await?;
+
+    // Populate the state with pools, liquidity and an approved compose hash. There are more pools
+    // than the upgrade migrates. The initial version verifies attestations at the wall-clock time,
+    // after the sample collateral expired, so the migration of workers is unit-tested instead.
+    create_liquidity_pools_v0(&solver_registry, &wnear, &usdc, 12).await?;
+    let result = owner
+        .call(solver_registry.id(), "approve_compose_hash")
+        .args_json(json!({ "compose_hash": COMPOSE_HASH }))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    let _ = ft_transfer(
+        &wnear,
+        wnear.as_account(),
+        &alice,
+        NearToken::from_near(10).as_yoctonear(),
+    )
+    .await?;
+    let _ = ft_transfer(&usdc, usdc.as_account(), &alice, 50_000_000).await?;
+    let _ = deposit_into_pool(
+        &solver_registry,
+        &alice,
+        0,
+        &wnear,
+        NearToken::from_near(10).as_yoctonear(),
+    )
+    .await?;
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 50_000_000).await?;
+
+    let pool_before = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(
+        pool_before.amounts[0].0,
+        NearToken::from_near(10).as_yoctonear()
+    );
+
+    // Upgrade and migrate the state
+    println!("Upgrading the Solver Registry contract...");
+    let wasm = std::fs::read(SOLVER_REGISTRY_CONTRACT_WASM).expect("Contract wasm not found");
+    let result = upgrade(&owner, &solver_registry, &wasm).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert_eq!(get_state_version(&solver_registry).await?, 1);
+
+    // The pools beyond the first batch are migrated separately
+    let result = solver_registry
+        .view("get_pool")
+        .args_json(json!({ "pool_id": 11 }))
+        .await;
+    assert!(
+        format!("{:?}", result.unwrap_err()).contains("Pool not migrated"),
+        "The pool shouldn't be accessible before its migration"
+    );
+    let result = migrate_pools(&alice, &solver_registry, None).await?;
+    assert!(!result.is_success(), "Pools are migrated by the upgrader");
+    let result = migrate_pools(&owner, &solver_registry, Some(1)).await?;
+    assert!(!result.json::<bool>()?, "One pool should remain");
+    let result = migrate_pools(&owner, &solver_registry, None).await?;
+    assert!(result.json::<bool>()?, "All pools should be migrated");
+    let result = migrate_pools(&owner, &solver_registry, None).await?;
+    assert!(!result.is_success(), "No migration should be pending");
+    assert!(get_pool_info(&solver_registry, 11)
+        .await?
+        .worker_id
+        .is_none());
+
+    let owner_id: near_sdk::AccountId = solver_registry.view("get_owner_id").await?.json()?;
+    assert_eq!(&owner_id, owner.id());
+    let compose_hashes: Vec<String> = solver_registry
+        .view("get_approved_compose_hashes")
+        .await?
+        .json()?;
+    assert_eq!(compose_hashes, vec![COMPOSE_HASH.to_string()]);
+    let worker_ping_timeout_ms: u64 = solver_registry
+        .view("get_worker_ping_timeout_ms")
+        .await?
+        .json()?;
+    assert_eq!(worker_ping_timeout_ms, 10 * 60 * 1000);
+
+    let pool_after = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool_after.token_ids, pool_before.token_ids);
+    assert_eq!(pool_after.amounts, pool_before.amounts);
+    assert_eq!(pool_after.fee, pool_before.fee);
+    assert_eq!(
+        pool_after.shares_total_supply,
+        pool_before.shares_total_supply
+    );
+    assert_eq!(pool_after.worker_id, pool_before.worker_id);
+    assert_eq!(
+        pool_after.last_ping_timestamp_ms,
+        pool_before.last_ping_timestamp_ms
+    );
+    assert!(pool_after.app_compose_policy.is_none());
+    assert!(pool_after.vault_code_hash.is_none());
+    assert!(pool_after.worker_ping_timeout_ms.is_none());
+
+    // Workers register and ping on the migrated state, and new features work on it
+    let result =
+        set_attestation_timestamp_ms(&owner, &solver_registry, SAMPLE_ATTESTATION_TIMESTAMP_MS)
+            .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = ping_worker(&alice, &solver_registry).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(solver_registry
+        .view("get_intents_vault_code_hash")
+        .await?
+        .json::<Option<Base58CryptoHash>>()?
+        .is_none());
+    let result = solver_registry
+        .call("create_liquidity_pool")
+        .args_json(json!({
+            "token_ids": [wnear.id(), usdc.id()],
+            "fee": 300
+        }))
+        .deposit(NearToken::from_millinear(100))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Pool creation should fail before the vault code is published"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Intents vault code not published"));
+
+    let result = publish_intents_vault_code(&owner, &solver_registry).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    let pool_len: u32 = solver_registry.view("get_pool_len").await?.json()?;
+    assert_eq!(pool_len, 13);
+
+    // The vaults of the initial version have no code hash and can't be upgraded
+    let result = owner
+        .call(solver_registry.id(), "upgrade_pool_vault")
+        .args_json(json!({ "pool_id": 0 }))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "The vault of the initial version can't be upgraded"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Pool vault has no code hash and can't be upgraded"));
+
+    let result = owner
+        .call(solver_registry.id(), "upgrade_all_pool_vaults")
+        .args_json(json!({ "limit": 20 }))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(
+        !result
+            .logs()
+            .iter()
+            .any(|log| log.contains("Failed to upgrade the vault")),
+        "The vaults with no code hash should be skipped"
+    );
+    assert!(get_pool_info(&solver_registry, 0)
+        .await?
+        .vault_code_hash
+        .is_none());
+
+    // Upgrading to the same state version keeps the state as is
+    let result = stage_upgrade(&owner, &solver_registry, &wasm).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = upgrade(&owner, &solver_registry, &wasm).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert_eq!(get_state_version(&solver_registry).await?, 1);
+    let pool_after_reupgrade = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool_after_reupgrade.amounts, pool_before.amounts);
+    assert!(get_worker_info(&solver_registry, &alice).await?.is_some());
+
+    println!("Test passed: State of version 0 survives the upgrade");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_new_contract_has_current_state_version() -> Result<(), Box<dyn std::error::Error>> {
+    let sandbox = near_workspaces::sandbox().await?;
+    let (_wnear, _usdc, _owner, _alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    assert_eq!(get_state_version(&solver_registry).await?, 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upgrade_requires_staged_code_hash() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for staged upgrades...");
+    let sandbox = near_workspaces::sandbox().await?;
+    let (_wnear, _usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+    let wasm = std::fs::read(SOLVER_REGISTRY_CONTRACT_WASM).expect("Contract wasm not found");
+
+    // The code can't be deployed without staging its hash
+    let result = upgrade(&owner, &solver_registry, &wasm).await?;
+    assert!(!result.is_success(), "Upgrade should fail without staging");
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Upgrade not staged for the code hash"));
+
+    // Only the owner or an upgrader can stage upgrades
+    let result = stage_upgrade(&alice, &solver_registry, &wasm).await?;
+    assert!(!result.is_success(), "Staging should fail without the role");
+
+    let result = stage_upgrade(&owner, &solver_registry, &wasm).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = stage_upgrade(&owner, &solver_registry, &wasm).await?;
+    assert!(!result.is_success(), "Upgrade can't be staged twice");
+
+    // Code other than the staged one is rejected
+    let mut other_wasm = wasm.clone();
+    other_wasm.extend_from_slice(&[0]);
+    let result = upgrade(&owner, &solver_registry, &other_wasm).await?;
+    assert!(
+        !result.is_success(),
+        "Upgrade should fail for unstaged code"
+    );
+
+    // The staged code is deployed and the new code hash is emitted with the source metadata
+    let result = upgrade(&owner, &solver_registry, &wasm).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let code_hash = code_hash(&wasm);
+    let upgraded_log = result
+        .logs()
+        .into_iter()
+        .find(|log| log.contains("contract_upgraded"))
+        .expect("Upgrade should emit an event");
+    assert!(upgraded_log.contains(&String::from(&code_hash)));
+    assert!(upgraded_log.contains("source_metadata"));
+
+    // The staged upgrade is consumed
+    let result = upgrade(&owner, &solver_registry, &wasm).await?;
+    assert!(!result.is_success(), "Staged upgrade can be executed once");
+
+    println!("Test passed: Only staged code can be deployed");
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::process::Command;
+use std::str::FromStr;
+
+use common::constants::*;
+use near_workspaces::types::SecretKey;
+
+const VERIFY_CLI: &str = env!("CARGO_BIN_EXE_solver-registry-verify");
+const SAMPLES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/samples/alice");
+
+// Helper function to run the verifier and get whether it succeeded with its output
+fn run_verify_cli(args: &[&str]) -> (bool, String) {
+    let output = Command::new(VERIFY_CLI).args(args).output().unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn test_verify_sample_attestation() {
+    println!("Starting test for the offline verification of the sample attestation...");
+    let quote_path = format!("{}/quote_alice.hex", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(&quote_path, QUOTE_HEX_ALICE).unwrap();
+    let collateral_path = format!("{}/quote_collateral.json", SAMPLES_DIR);
+    let tcb_info_path = format!("{}/tcb_info.json", SAMPLES_DIR);
+    let public_key = SecretKey::from_str(SECRET_KEY_ALICE)
+        .unwrap()
+        .public_key()
+        .to_string();
+    let timestamp_s = (SAMPLE_ATTESTATION_TIMESTAMP_MS / 1000).to_string();
+    let args = [
+        "--quote",
+        quote_path.as_str(),
+        "--collateral",
+        collateral_path.as_str(),
+        "--tcb-info",
+        tcb_info_path.as_str(),
+        "--public-key",
+        public_key.as_str(),
+        "--timestamp",
+        timestamp_s.as_str(),
+        "--pre-launch-script-hash",
+        PRE_LAUNCH_SCRIPT_HASH,
+    ];
+
+    // All stages pass with the approved compose hash
+    let (passed, output) =
+        run_verify_cli(&[&args[..], &["--compose-hash", COMPOSE_HASH][..]].concat());
+    assert!(passed, "{}", output);
+    assert!(!output.contains("[FAIL]"), "{}", output);
+    assert!(output.contains(&format!(
+        "[PASS] compose-hash\n    expected: {}\n",
+        COMPOSE_HASH
+    )));
+    assert!(output.contains("Attestation verification passed"));
+
+    // No compose hash is accepted if none is approved, and the other stages still run
+    let (passed, output) = run_verify_cli(&args);
+    assert!(!passed, "{}", output);
+    assert!(output.contains("[FAIL] compose-hash\n    expected: none\n"));
+    assert!(output.contains("[PASS] app-compose"));
+    assert!(output.contains("[PASS] docker-image-hash\n    expected: not verified\n"));
+    assert!(output.contains("Attestation verification failed"));
+
+    // The wrong public key fails the report data stage only
+    let bob_public_key = SecretKey::from_str(SECRET_KEY_BOB)
+        .unwrap()
+        .public_key()
+        .to_string();
+    let mut args_with_bob_key = [&args[..], &["--compose-hash", COMPOSE_HASH][..]].concat();
+    args_with_bob_key[7] = bob_public_key.as_str();
+    let (passed, output) = run_verify_cli(&args_with_bob_key);
+    assert!(!passed, "{}", output);
+    assert_eq!(output.matches("[FAIL]").count(), 1, "{}", output);
+    assert!(output.contains("[FAIL] report-data"));
+
+    println!("Test passed: The sample attestation is verified offline");
+}
+
+#[test]
+fn test_sample_compose_hash_and_event_log() {
+    println!("Starting test for the compose hash and event log of the sample...");
+    let tcb_info_path = format!("{}/tcb_info.json", SAMPLES_DIR);
+
+    let (passed, output) = run_verify_cli(&["compose-hash", &tcb_info_path]);
+    assert!(passed, "{}", output);
+    assert!(output.contains(&format!(
+        "Docker compose hash (approve_compose_hash): {}",
+        COMPOSE_HASH
+    )));
+
+    let (passed, output) = run_verify_cli(&["event-log", &tcb_info_path]);
+    assert!(passed, "{}", output);
+    assert!(output.contains("Event log replay passed"));
+
+    println!("Test passed: The sample's compose hash and event log are verified");
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::constants::*;
+use common::utils::*;
+
+#[tokio::test]
+async fn test_refresh_worker_attestation() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for worker attestation refresh...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Approve compose hash and the pre-launch script of the samples
+    approve_compose_hash(&owner, &solver_registry).await?;
+    approve_sample_pre_launch_script(&owner, &solver_registry).await?;
+    allow_legacy_report_data(&owner, &solver_registry).await?;
+
+    // Only the owner can set the max collateral age, and it can't be zero
+    let result = set_max_collateral_age_ms(&alice, &solver_registry, 60 * 1000).await?;
+    assert!(
+        !result.is_success(),
+        "Non-owner can't set max collateral age"
+    );
+    let result = set_max_collateral_age_ms(&owner, &solver_registry, 0).await?;
+    assert!(!result.is_success(), "Max collateral age can't be zero");
+
+    // Register Alice as a worker
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let worker = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should be registered as a worker");
+    assert_eq!(
+        worker.attestation_valid_until_ms,
+        SAMPLE_COLLATERAL_NEXT_UPDATE_MS
+    );
+
+    // Unregistered worker can't refresh its attestation
+    let result = refresh_worker_attestation(
+        &bob,
+        &solver_registry,
+        QUOTE_HEX_BOB,
+        QUOTE_COLLATERAL_BOB,
+        TCB_INFO_BOB,
+    )
+    .await?;
+    assert!(
+        !result.is_success(),
+        "Attestation refresh should fail for an unregistered worker"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Worker not found"));
+
+    // The registered worker refreshes its attestation
+    let result = refresh_worker_attestation(
+        &alice,
+        &solver_registry,
+        QUOTE_HEX_ALICE,
+        QUOTE_COLLATERAL_ALICE,
+        TCB_INFO_ALICE,
+    )
+    .await?;
+    assert!(
+        result.is_success(),
+        "Attestation refresh should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(result
+        .logs()
+        .iter()
+        .any(|log| log.contains("worker_attestation_refreshed")));
+
+    // Collateral older than the max collateral age is rejected
+    let result = set_max_collateral_age_ms(&owner, &solver_registry, 1000).await?;
+    assert!(result.is_success(), "Owner can set max collateral age");
+    let result = refresh_worker_attestation(
+        &alice,
+        &solver_registry,
+        QUOTE_HEX_ALICE,
+        QUOTE_COLLATERAL_ALICE,
+        TCB_INFO_ALICE,
+    )
+    .await?;
+    assert!(
+        !result.is_success(),
+        "Attestation refresh should fail with stale collateral"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Attestation verification failed"));
+
+    // The worker keeps its previous attestation
+    let worker_after = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should still be registered as a worker");
+    assert_eq!(
+        worker_after.attestation_valid_until_ms,
+        worker.attestation_valid_until_ms
+    );
+
+    // The worker can't ping once its attestation expired
+    let result = ping_worker(&alice, &solver_registry).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result =
+        set_attestation_timestamp_ms(&owner, &solver_registry, SAMPLE_COLLATERAL_NEXT_UPDATE_MS)
+            .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = ping_worker(&alice, &solver_registry).await?;
+    assert!(
+        !result.is_success(),
+        "Ping should fail once the attestation expired"
+    );
+    let error = result.into_result().unwrap_err();
+    assert!(format!("{:?}", error).contains("Worker attestation expired"));
+
+    println!("Test passed: Worker attestation refresh is validated");
+    Ok(())
+}
@@ -21,7 +21,7 @@ near view $USDC_TOKEN ft_balance_of '{"account_id":"'$SOLVER_TESTER_ACCOUNT'"}'
 # --- create pool ---
 
 # create liquidity pool
-near call $SOLVER_REGISTRY_CONTRACT create_liquidity_pool '{"token_ids":["'$WNEAR_TOKEN'","'$USDC_TOKEN'"],"fee":100}' --accountId $SOLVER_TESTER_ACCOUNT --deposit 1.5 --gas 300000000000000
+near call $SOLVER_REGISTRY_CONTRACT create_liquidity_pool '{"token_ids":["'$WNEAR_TOKEN'","'$USDC_TOKEN'"],"fee":100}' --accountId $SOLVER_TESTER_ACCOUNT --deposit 0.1 --gas 300000000000000
 
 # register accounts
 near call $WNEAR_TOKEN storage_deposit '{"account_id":"'$SOLVER_REGISTRY_CONTRACT'","registration_only":true}' --accountId $SOLVER_TESTER_ACCOUNT --deposit 0.00125
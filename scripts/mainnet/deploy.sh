@@ -5,3 +5,6 @@ export SOLVER_GOV_ACCOUNT=solver-gov.near
 
 # deploy solver registry contract
 near deploy $SOLVER_REGISTRY_CONTRACT ../../contracts/solver-registry/res/solver_registry.wasm --initFunction new --initArgs '{"owner_id":"'$SOLVER_GOV_ACCOUNT'","intents_contract_id":"'$INTENTS_CONTRACT'"}'
+
+# publish the intents vault code as a global contract for the pools' accounts
+near call $SOLVER_REGISTRY_CONTRACT publish_intents_vault_code '{}' --accountId $SOLVER_GOV_ACCOUNT --deposit 15 --gas 300000000000000
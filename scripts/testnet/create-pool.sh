@@ -9,7 +9,7 @@ export POOL_ID=0
 export POOL_CONTRACT='pool-'$POOL_ID'.'$SOLVER_REGISTRY_CONTRACT
 
 # create liquidity pool
-near call $SOLVER_REGISTRY_CONTRACT create_liquidity_pool '{"token_ids":["'$WNEAR_TOKEN'","'$USDC_TOKEN'"],"fee":100}' --accountId $SOLVER_TESTER_ACCOUNT --deposit 1.5 --gas 300000000000000
+near call $SOLVER_REGISTRY_CONTRACT create_liquidity_pool '{"token_ids":["'$WNEAR_TOKEN'","'$USDC_TOKEN'"],"fee":100}' --accountId $SOLVER_TESTER_ACCOUNT --deposit 0.1 --gas 300000000000000
 
 # deposit NEAR for wNEAR
 near call $WNEAR_TOKEN near_deposit '{}' --accountId $SOLVER_TESTER_ACCOUNT --deposit 2